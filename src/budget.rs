@@ -0,0 +1,91 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Caps how many bytes of feed files, sized on disk, are read into memory at once
+ *
+*/
+
+use std::sync::{Condvar, Mutex};
+
+pub struct ByteBudget {
+    limit: u64,
+    used: Mutex<u64>,
+    freed: Condvar,
+}
+
+// Bytes taken from a budget, handed back when dropped
+pub struct Permit<'a> {
+    budget: &'a ByteBudget,
+    bytes: u64,
+}
+
+impl ByteBudget {
+    pub fn new(limit: u64) -> ByteBudget {
+        ByteBudget {
+            limit,
+            used: Mutex::new(0),
+            freed: Condvar::new(),
+        }
+    }
+
+    // Block until bytes fit under the limit. A file bigger than the whole budget
+    // waits until nothing else is in flight and then runs alone.
+    pub fn acquire(&self, bytes: u64) -> Permit<'_> {
+        let bytes = bytes.min(self.limit);
+        let mut used = self.used.lock().unwrap();
+        while *used + bytes > self.limit {
+            used = self.freed.wait(used).unwrap();
+        }
+
+        *used += bytes;
+        Permit {
+            budget: self,
+            bytes,
+        }
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        *self.budget.used.lock().unwrap() -= self.bytes;
+        self.budget.freed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn permits_are_handed_back() {
+        let budget = ByteBudget::new(10);
+        let first = budget.acquire(6);
+        let second = budget.acquire(4);
+        assert_eq!(*budget.used.lock().unwrap(), 10);
+        drop(first);
+        drop(second);
+        assert_eq!(*budget.used.lock().unwrap(), 0);
+
+        // Bigger than the whole budget runs alone instead of waiting forever
+        let huge = budget.acquire(100);
+        assert_eq!(huge.bytes, 10);
+    }
+
+    #[test]
+    fn acquire_waits_for_room() {
+        let budget = ByteBudget::new(10);
+        let acquired = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let held = budget.acquire(8);
+            scope.spawn(|| {
+                let _permit = budget.acquire(5);
+                acquired.store(true, Ordering::SeqCst);
+            });
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!acquired.load(Ordering::SeqCst));
+            drop(held);
+        });
+        assert!(acquired.load(Ordering::SeqCst));
+    }
+}
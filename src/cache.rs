@@ -0,0 +1,235 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * The cache is a directory with one file per record, named by md5
+ *
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::entry::{Entry, Record};
+use crate::format::Format;
+use crate::Result;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Written {
+    New,
+    // Same content already in the cache, nothing was written
+    Duplicate,
+}
+
+pub struct Cache {
+    pub dir: PathBuf,
+    pub format: Format,
+    // Leading md5 characters used as a subdirectory, 0 keeps the cache flat
+    pub shard_chars: usize,
+}
+
+impl Cache {
+    pub fn new(dir: &Path, format: Format, shard_chars: usize) -> Cache {
+        Cache {
+            dir: dir.to_path_buf(),
+            format,
+            shard_chars,
+        }
+    }
+
+    // Write a record to <dir>/[<shard>/]<md5>.<ext>, skipping records already cached
+    pub fn write(&self, record: &Record) -> Result<Written> {
+        let bytes = self.format.encode(record)?;
+        let digest = get_md5_hash(&bytes);
+        let mut dir = self.dir.clone();
+        if self.shard_chars > 0 {
+            dir.push(&digest[..self.shard_chars]);
+            std::fs::create_dir_all(&dir)?;
+        }
+
+        let file_path = dir.join(format!("{}.{}", digest, self.format.extension()));
+        if file_path.exists() {
+            return Ok(Written::Duplicate);
+        }
+
+        std::fs::write(file_path, bytes)?;
+        Ok(Written::New)
+    }
+
+    // Every record file in the cache whatever the format or sharding it was written with
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        collect_record_files(&self.dir, &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+
+    // Every record in the cache; unreadable files are returned as errors next to their path
+    pub fn read(&self) -> Result<Vec<(PathBuf, Result<Record>)>> {
+        Ok(self
+            .files()?
+            .into_iter()
+            .map(|file| {
+                let record = read_record(&file);
+                (file, record)
+            })
+            .collect())
+    }
+}
+
+fn collect_record_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_record_files(&path, files)?;
+        } else if record_format(&path).is_some() {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn record_format(path: &Path) -> Option<Format> {
+    Format::from_extension(path.extension()?.to_str()?)
+}
+
+pub fn get_file_string(path: &Path) -> Result<String> {
+    Ok(std::fs::read_to_string(path)?)
+}
+
+// Compute md5 hash of a string
+pub fn get_md5_hash(s: impl AsRef<[u8]>) -> String {
+    format!("{:x}", md5::compute(s))
+}
+
+// md5 of a file read in chunks, matching get_md5_hash of its bytes
+pub fn get_file_md5(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut context = md5::Context::new();
+    let mut buf = [0; 64 * 1024];
+    loop {
+        match file.read(&mut buf)? {
+            0 => break,
+            n => context.consume(&buf[..n]),
+        }
+    }
+
+    Ok(format!("{:x}", context.compute()))
+}
+
+pub fn read_record(path: &Path) -> Result<Record> {
+    let format =
+        record_format(path).ok_or_else(|| format!("{} is not a cache record", path.display()))?;
+    format.decode(&std::fs::read(path)?)
+}
+
+// Entries whose video has not been tombstoned, in cache order
+pub fn live_entries(records: &[Record]) -> Vec<Entry> {
+    let deleted: HashSet<&str> = records
+        .iter()
+        .filter_map(|record| match record {
+            Record::Tombstone(tombstone) => Some(tombstone.video_id.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    records
+        .iter()
+        .filter_map(|record| match record {
+            Record::Entry(entry) if !deleted.contains(entry.video_id.as_str()) => {
+                Some(entry.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Position of every video seen in a playlist; a video that moved keeps the lowest
+pub fn playlist_positions(records: &[Record], playlist_id: &str) -> HashMap<String, u32> {
+    let mut positions = HashMap::new();
+    for record in records {
+        if let Record::Membership(membership) = record {
+            if membership.playlist_id == playlist_id {
+                let position = positions
+                    .entry(membership.video_id.clone())
+                    .or_insert(membership.position);
+                *position = membership.position.min(*position);
+            }
+        }
+    }
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::{Channel, Membership, Tombstone};
+
+    fn entry(video_id: &str) -> Record {
+        Record::Entry(Entry {
+            video_id: video_id.to_string(),
+            channel_id: "UCRijo3ddMTht_IHyNSNXpNQ".to_string(),
+            title: format!("Video {}", video_id),
+            author: "Dude Perfect".to_string(),
+            published: "2022-11-05T13:59:57+00:00".parse().unwrap(),
+            updated: None,
+            description: None,
+            kind: None,
+            views: None,
+            stream: None,
+            feed: None,
+            links: Vec::new(),
+            hashtags: Vec::new(),
+            mentions: Vec::new(),
+            chapters: Vec::new(),
+            sponsored: false,
+            sponsors: Vec::new(),
+            language: None,
+            title_normalized: None,
+        })
+    }
+
+    #[test]
+    fn deleted_videos_are_left_out() {
+        let records = [
+            entry("Ab3CdEfGhIj"),
+            entry("Kl4MnOpQrSt"),
+            Record::Tombstone(Tombstone {
+                video_id: "Ab3CdEfGhIj".to_string(),
+                channel_id: None,
+                deleted: "2022-11-06T10:00:00+00:00".to_string(),
+            }),
+            Record::Channel(Channel {
+                channel_id: "UCRijo3ddMTht_IHyNSNXpNQ".to_string(),
+                feed_title: "Dude Perfect".to_string(),
+            }),
+        ];
+        let live: Vec<String> = live_entries(&records)
+            .into_iter()
+            .map(|entry| entry.video_id)
+            .collect();
+        assert_eq!(live, ["Kl4MnOpQrSt"]);
+    }
+
+    #[test]
+    fn playlist_positions_keep_the_lowest() {
+        let membership = |playlist_id: &str, video_id: &str, position| {
+            Record::Membership(Membership {
+                playlist_id: playlist_id.to_string(),
+                video_id: video_id.to_string(),
+                position,
+            })
+        };
+        let records = [
+            membership("PLa", "Ab3CdEfGhIj", 4),
+            membership("PLa", "Ab3CdEfGhIj", 1),
+            membership("PLa", "Kl4MnOpQrSt", 0),
+            membership("PLb", "Ab3CdEfGhIj", 0),
+            entry("Ab3CdEfGhIj"),
+        ];
+        let positions = playlist_positions(&records, "PLa");
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions["Ab3CdEfGhIj"], 1);
+        assert_eq!(positions["Kl4MnOpQrSt"], 0);
+    }
+}
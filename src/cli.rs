@@ -0,0 +1,1051 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Command line interface shared by the binaries
+ *
+*/
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use rayon::prelude::*;
+use serde::Serialize;
+use tracing::{debug, error, info, warn};
+
+use crate::budget::ByteBudget;
+use crate::cache::{self, Cache};
+use crate::config::{Config, LogConfig};
+use crate::discover::{Discovery, SymlinkPolicy};
+use crate::entry::{Entry, Record};
+use crate::fetch;
+use crate::format::Format;
+use crate::ingest::{ingest_file, write_feed, FileStats, IncompletePolicy, Outcome, Pipeline};
+use crate::kind::{Kind, KindOverrides};
+use crate::language;
+use crate::log::{self, LogFormat};
+use crate::manifest::{self, Ingested, Manifest};
+use crate::opml;
+use crate::progress::{Progress, ProgressMode};
+use crate::sink::Sink;
+use crate::source;
+use crate::stream::{self, StreamState};
+use crate::summary::Summary;
+use crate::title::TitleCleaner;
+use crate::watch::Watcher;
+use crate::Result;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Fetch youtube rss feeds and cache them by entry")]
+pub struct Cli {
+    /// Config file, defaults to $YOUTUBE_RSS_CONFIG or ./youtube-rss-cached.toml
+    #[arg(long, global = true, value_parser = existing_file)]
+    pub config: Option<PathBuf>,
+    /// Log filter such as info or youtube_rss_cached=debug; overrides log.level
+    #[arg(long, global = true)]
+    pub log_level: Option<String>,
+    /// Log line format on stderr; overrides log.format
+    #[arg(long, global = true)]
+    pub log_format: Option<LogFormat>,
+    /// Parser threads; overrides threads
+    #[arg(long, global = true)]
+    pub threads: Option<usize>,
+    /// Most bytes of feed files, as sized on disk, read at once; overrides max_inflight_bytes.
+    /// Compressed files and archives take more than this once decompressed
+    #[arg(long, global = true)]
+    pub max_inflight_bytes: Option<u64>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Parse every feed file in the input directories into the cache
+    Parse {
+        /// Directory of feed files, repeatable, or - for stdin; overrides input.dirs
+        #[arg(long, value_parser = input_dir)]
+        src: Vec<PathBuf>,
+        /// Cache directory to write records into, or - for stdout; overrides output.dir
+        #[arg(long, value_parser = output_dir)]
+        dst: Option<PathBuf>,
+        /// Record encoding; overrides output.format
+        #[arg(long)]
+        format: Option<Format>,
+        /// Reprocess every file, even those the ingest manifest says are unchanged
+        #[arg(long)]
+        full: bool,
+        /// Glob of files to read, repeatable; overrides input.include
+        #[arg(long)]
+        include: Vec<String>,
+        /// Glob of files to leave out, repeatable; overrides input.exclude
+        #[arg(long)]
+        exclude: Vec<String>,
+        /// How to treat symbolic links; overrides input.symlinks
+        #[arg(long)]
+        symlinks: Option<SymlinkPolicy>,
+        /// Progress display; overrides log.progress
+        #[arg(long)]
+        progress: Option<ProgressMode>,
+        /// What to do with entries missing fields; overrides input.incomplete
+        #[arg(long)]
+        incomplete: Option<IncompletePolicy>,
+        /// Directory for quarantined entries; overrides input.quarantine
+        #[arg(long, value_parser = existing_dir)]
+        quarantine: Option<PathBuf>,
+    },
+    /// Parse feed files as they land in a spool directory, until interrupted
+    Watch {
+        /// Spool directory to watch; defaults to the first input.dirs
+        #[arg(long, value_parser = existing_dir)]
+        src: Option<PathBuf>,
+        /// Cache directory to write records into, or - for stdout; overrides output.dir
+        #[arg(long, value_parser = output_dir)]
+        dst: Option<PathBuf>,
+        /// Record encoding; overrides output.format
+        #[arg(long)]
+        format: Option<Format>,
+        /// Move parsed files here; overrides watch.processed
+        #[arg(long, value_parser = existing_dir)]
+        processed: Option<PathBuf>,
+        /// Move files that failed to parse here; overrides watch.failed
+        #[arg(long, value_parser = existing_dir)]
+        failed: Option<PathBuf>,
+    },
+    /// Download the feed of every configured channel
+    Fetch {
+        /// Extra channel list, one channel id per line
+        #[arg(long, value_parser = existing_file)]
+        channels: Option<PathBuf>,
+        /// Directory to save feed xml files into; overrides fetch.dst
+        #[arg(long, value_parser = existing_dir)]
+        dst: Option<PathBuf>,
+    },
+    /// Write every entry that has not been deleted as json lines
+    Export {
+        /// Cache directory to read; overrides output.dir
+        #[arg(long, value_parser = existing_dir)]
+        cache: Option<PathBuf>,
+        /// Output file, stdout when omitted
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Only entries detected in this ISO 639-3 language, repeatable
+        #[arg(long, value_parser = language_code)]
+        language: Vec<String>,
+        /// Only entries whose language was detected with at least this confidence, 0 to 100
+        #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+        min_confidence: Option<u8>,
+    },
+    /// Print the entries matching every given filter as json lines
+    Query {
+        /// Cache directory to read; overrides output.dir
+        #[arg(long, value_parser = existing_dir)]
+        cache: Option<PathBuf>,
+        /// Only entries from this channel id
+        #[arg(long)]
+        channel: Option<String>,
+        /// Only entries for this video id
+        #[arg(long)]
+        video: Option<String>,
+        /// Only entries whose title contains this text, ignoring case; cleaned up the
+        /// way title_normalized is and matched against it too
+        #[arg(long)]
+        title: Option<String>,
+        /// Only entries in this playlist, in playlist order
+        #[arg(long)]
+        playlist: Option<String>,
+        /// Only shorts or only regular videos, after kind overrides
+        #[arg(long, value_enum)]
+        kind: Option<Kind>,
+        /// Only snapshots of streams and premieres in this state
+        #[arg(long, value_enum)]
+        stream: Option<StreamState>,
+        /// Only entries linking to this domain or its subdomains
+        #[arg(long)]
+        domain: Option<String>,
+        /// Only entries with this hashtag in their description, without the #
+        #[arg(long)]
+        hashtag: Option<String>,
+        /// Only entries a sponsor rule matched, or with false only those none did
+        #[arg(long)]
+        sponsored: Option<bool>,
+        /// Only entries detected in this ISO 639-3 language, repeatable
+        #[arg(long, value_parser = language_code)]
+        language: Vec<String>,
+        /// Only entries whose language was detected with at least this confidence, 0 to 100
+        #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+        min_confidence: Option<u8>,
+    },
+    /// Print how often each channel's videos name each sponsor, as json lines
+    Sponsors {
+        /// Cache directory to read; overrides output.dir
+        #[arg(long, value_parser = existing_dir)]
+        cache: Option<PathBuf>,
+        /// Only this channel id
+        #[arg(long)]
+        channel: Option<String>,
+    },
+    /// Print the channels whose entries link to a domain as json lines
+    Links {
+        /// Cache directory to read; overrides output.dir
+        #[arg(long, value_parser = existing_dir)]
+        cache: Option<PathBuf>,
+        /// Domain linked to, subdomains included
+        #[arg(long)]
+        domain: String,
+    },
+    /// Print every change of stream state between cached snapshots as json lines
+    Streams {
+        /// Cache directory to read; overrides output.dir
+        #[arg(long, value_parser = existing_dir)]
+        cache: Option<PathBuf>,
+        /// Only videos from this channel id
+        #[arg(long)]
+        channel: Option<String>,
+    },
+    /// Check that every cache file decodes and is named after its md5
+    Verify {
+        /// Cache directory to check; overrides output.dir
+        #[arg(long, value_parser = existing_dir)]
+        cache: Option<PathBuf>,
+    },
+    /// Import or export channel subscriptions as OPML
+    #[command(subcommand)]
+    Opml(OpmlCommand),
+    /// Inspect the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OpmlCommand {
+    /// Add the channels of an OPML file to a channel list
+    Import {
+        /// OPML file from a feed reader
+        #[arg(long, value_parser = existing_file)]
+        opml: PathBuf,
+        /// Channel list to update, created when missing; defaults to the first channels.lists
+        #[arg(long)]
+        channels: Option<PathBuf>,
+    },
+    /// Write the channels seen in the cache as OPML
+    Export {
+        /// Cache directory to read; overrides output.dir
+        #[arg(long, value_parser = existing_dir)]
+        cache: Option<PathBuf>,
+        /// OPML file to write
+        #[arg(long)]
+        opml: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Validate the configuration and print the effective settings
+    Check,
+}
+
+fn existing_dir(s: &str) -> std::result::Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+    if path.is_dir() {
+        Ok(path)
+    } else {
+        Err(format!("{} is not a directory", s))
+    }
+}
+
+fn language_code(s: &str) -> std::result::Result<String, String> {
+    match language::lang(s) {
+        Some(lang) => Ok(lang.code().to_string()),
+        None => Err(format!("{} is not an ISO 639-3 code, e.g. eng or deu", s)),
+    }
+}
+
+// Stands for stdin as a source and stdout as a destination
+const STDIO: &str = "-";
+
+fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == STDIO
+}
+
+fn input_dir(s: &str) -> std::result::Result<PathBuf, String> {
+    if s == STDIO {
+        return Ok(PathBuf::from(s));
+    }
+    existing_dir(s)
+}
+
+fn output_dir(s: &str) -> std::result::Result<PathBuf, String> {
+    input_dir(s)
+}
+
+fn existing_file(s: &str) -> std::result::Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(format!("{} is not a file", s))
+    }
+}
+
+// Settings after merging the config with the command line
+struct Context {
+    config: Config,
+    default_format: Format,
+}
+
+impl Context {
+    // Cache directory from the flag, falling back to output.dir
+    fn cache(&self, flag: Option<PathBuf>) -> Result<Cache> {
+        let dir = flag
+            .or_else(|| self.config.output.dir.clone())
+            .ok_or("no cache directory, pass --cache/--dst or set output.dir")?;
+        Ok(Cache::new(
+            &dir,
+            self.config.format(self.default_format),
+            self.config.output.shard_chars,
+        ))
+    }
+
+    // Cache directory or stdout, from the flag falling back to output.dir
+    fn sink(&self, flag: Option<PathBuf>) -> Result<Sink> {
+        match flag {
+            Some(dst) if is_stdio(&dst) => {
+                Ok(Sink::Stdout(self.config.format(self.default_format)))
+            }
+            dst => Ok(Sink::Cache(self.cache(dst)?)),
+        }
+    }
+
+    // Manifest configured in input.manifest, or kept in the cache directory.
+    // Stdout has nowhere to keep one, so None there.
+    fn manifest_path(&self, sink: &Sink) -> Option<PathBuf> {
+        match (&self.config.input.manifest, sink) {
+            (Some(path), _) => Some(path.clone()),
+            (None, Sink::Cache(cache)) => Some(cache.dir.join(manifest::DEFAULT_NAME)),
+            (None, Sink::Stdout(_)) => None,
+        }
+    }
+
+    // Records that could be read from the cache, complaining about the rest
+    fn read_records(&self, flag: Option<PathBuf>) -> Result<Vec<Record>> {
+        let mut records = Vec::new();
+        for (file, record) in self.cache(flag)?.read()? {
+            match record {
+                Ok(record) => records.push(record),
+                Err(e) => warn!(file = %file.display(), error = %e, "skipping unreadable record"),
+            }
+        }
+
+        Ok(records)
+    }
+}
+
+fn write_entries(out: Option<&Path>, entries: &[Entry]) -> Result<()> {
+    let mut writer: Box<dyn Write> = match out {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    for entry in entries {
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+// Parse flags that override the config
+struct ParseArgs {
+    src: Vec<PathBuf>,
+    dst: Option<PathBuf>,
+    format: Option<Format>,
+    full: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    symlinks: Option<SymlinkPolicy>,
+    progress: Option<ProgressMode>,
+    incomplete: Option<IncompletePolicy>,
+    quarantine: Option<PathBuf>,
+}
+
+fn run_parse(mut ctx: Context, args: ParseArgs) -> Result<()> {
+    let ParseArgs {
+        src,
+        dst,
+        format,
+        full,
+        include,
+        exclude,
+        symlinks,
+        progress,
+        incomplete,
+        quarantine,
+    } = args;
+    if format.is_some() {
+        ctx.config.output.format = format;
+    }
+    if !include.is_empty() {
+        ctx.config.input.include = include;
+    }
+    if !exclude.is_empty() {
+        ctx.config.input.exclude = exclude;
+    }
+    if let Some(symlinks) = symlinks {
+        ctx.config.input.symlinks = symlinks;
+    }
+    if let Some(progress) = progress {
+        ctx.config.log.progress = progress;
+    }
+    if let Some(incomplete) = incomplete {
+        ctx.config.input.incomplete = incomplete;
+    }
+    if quarantine.is_some() {
+        ctx.config.input.quarantine = quarantine;
+    }
+    report_problems(&ctx.config.validate())?;
+    let pipeline = Pipeline::new(&ctx.config)?;
+    let discovery = Discovery::new(&ctx.config.input)?;
+    let src = if src.is_empty() {
+        ctx.config.input.dirs.clone()
+    } else {
+        src
+    };
+    if src.is_empty() {
+        return Err("no input directories, pass --src or set input.dirs".into());
+    }
+    let sink = ctx.sink(dst)?;
+    if src.iter().any(|dir| is_stdio(dir)) {
+        if src.len() > 1 {
+            return Err("- can not be combined with other --src directories".into());
+        }
+        return parse_stdin(&sink, &pipeline);
+    }
+
+    let manifest_path = ctx.manifest_path(&sink);
+    let mut manifest = match &manifest_path {
+        Some(path) => Manifest::load(path)?,
+        None => Manifest::default(),
+    };
+
+    build_pool(&ctx.config)?;
+    let budget = ByteBudget::new(ctx.config.max_inflight_bytes);
+
+    // Get all matching files under the input directories
+    let files = discovery.all_files(&src)?;
+    info!(files = files.len(), "processing");
+
+    // Use Rayon to parse files in parallel
+    let mut summary = Summary::default();
+    let interval = Duration::from_secs(ctx.config.log.progress_secs);
+    let outcomes: Vec<Result<Outcome>> =
+        Progress::run(ctx.config.log.progress, interval, files.len(), |progress| {
+            files
+                .par_iter()
+                .map(|file| {
+                    let outcome = ingest_file(file, &manifest, full, &sink, &budget, &pipeline);
+                    progress.file_done(match &outcome {
+                        Ok(Outcome::Ingested(_, _, stats)) => stats.entries,
+                        _ => 0,
+                    });
+                    outcome
+                })
+                .collect()
+        });
+    sink.flush()?;
+
+    for (file, outcome) in files.iter().zip(outcomes) {
+        summary.add(file, &outcome);
+        match outcome {
+            Ok(Outcome::Touched(key, record)) | Ok(Outcome::Ingested(key, record, _)) => {
+                manifest.record(key, record)
+            }
+            Ok(Outcome::Unchanged) | Err(_) => (),
+        }
+    }
+    // Saved even when some files failed so their neighbours are not parsed again
+    if manifest_path.is_some() {
+        manifest.save()?;
+    }
+
+    summary.log();
+    if !summary.failures.is_empty() {
+        return Err(format!("{} of {} files failed", summary.failures.len(), files.len()).into());
+    }
+    Ok(())
+}
+
+// One or more concatenated feed documents on stdin, possibly compressed or a tar stream
+fn parse_stdin(sink: &Sink, pipeline: &Pipeline) -> Result<()> {
+    let mut summary = Summary::default();
+    let mut stats = FileStats::default();
+    let stdin = std::io::stdin().lock();
+    let outcome = source::for_each_feed(Path::new(STDIO), stdin, |_, feed| {
+        write_feed(feed, sink, pipeline, &mut stats)
+    })
+    .map(|_| Outcome::Ingested(PathBuf::from(STDIO), Ingested::default(), stats));
+    sink.flush()?;
+
+    summary.add(Path::new(STDIO), &outcome);
+    summary.log();
+    outcome.map(|_| ())
+}
+
+// Watch flags that override the config
+struct WatchArgs {
+    src: Option<PathBuf>,
+    dst: Option<PathBuf>,
+    format: Option<Format>,
+    processed: Option<PathBuf>,
+    failed: Option<PathBuf>,
+}
+
+fn build_pool(config: &Config) -> Result<()> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(config.threads)
+        .build_global()?;
+    debug!(threads = config.threads, "thread pool");
+    Ok(())
+}
+
+fn run_watch(mut ctx: Context, args: WatchArgs) -> Result<()> {
+    if args.format.is_some() {
+        ctx.config.output.format = args.format;
+    }
+    let spool = args
+        .src
+        .or_else(|| ctx.config.input.dirs.first().cloned())
+        .ok_or("no spool directory, pass --src or set input.dirs")?;
+    let discovery = Discovery::new(&ctx.config.input)?;
+    let sink = ctx.sink(args.dst)?;
+    let manifest_path = ctx.manifest_path(&sink);
+    let manifest = match &manifest_path {
+        Some(path) => Manifest::load(path)?,
+        None => Manifest::default(),
+    };
+
+    build_pool(&ctx.config)?;
+    let budget = ByteBudget::new(ctx.config.max_inflight_bytes);
+
+    Watcher {
+        spool,
+        discovery: &discovery,
+        sink: &sink,
+        manifest,
+        save_manifest: manifest_path.is_some(),
+        budget: &budget,
+        pipeline: &Pipeline::new(&ctx.config)?,
+        processed: args.processed.or(ctx.config.watch.processed.clone()),
+        failed: args.failed.or(ctx.config.watch.failed.clone()),
+    }
+    .run()
+}
+
+fn run_fetch(ctx: Context, channels: Option<PathBuf>, dst: Option<PathBuf>) -> Result<()> {
+    let dst = dst
+        .or_else(|| ctx.config.fetch.dst.clone())
+        .ok_or("no fetch directory, pass --dst or set fetch.dst")?;
+
+    let mut ids: BTreeSet<String> = ctx.config.channels.ids.iter().cloned().collect();
+    for list in ctx.config.channels.lists.iter().chain(channels.as_ref()) {
+        ids.extend(opml::read_channel_list(list)?);
+    }
+    if ids.is_empty() {
+        return Err("no channels, pass --channels or set channels.lists/channels.ids".into());
+    }
+
+    let agent = fetch::agent(&ctx.config.fetch);
+    let mut failed = 0;
+    for channel_id in &ids {
+        match fetch::fetch_channel(&agent, channel_id)
+            .and_then(|xml| fetch::write_feed(&dst, channel_id, &xml))
+        {
+            Ok(path) => info!(channel_id = %channel_id, file = %path.display(), "fetched"),
+            Err(e) => {
+                warn!(channel_id = %channel_id, error = %e, "fetch failed");
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} channels failed", failed, ids.len()).into());
+    }
+    Ok(())
+}
+
+// Detected in any of the languages, or any language when none are given, with at
+// least the confidence. Entries with no language only pass when neither is given.
+fn in_language(entry: &Entry, languages: &[String], min_confidence: Option<u8>) -> bool {
+    if languages.is_empty() && min_confidence.is_none() {
+        return true;
+    }
+    entry.language.as_ref().is_some_and(|language| {
+        (languages.is_empty() || languages.iter().any(|code| language.is(code)))
+            && min_confidence.is_none_or(|min| language.confidence >= min)
+    })
+}
+
+fn run_export(
+    ctx: Context,
+    cache: Option<PathBuf>,
+    out: Option<&Path>,
+    languages: &[String],
+    min_confidence: Option<u8>,
+) -> Result<()> {
+    let records = ctx.read_records(cache)?;
+    let entries: Vec<Entry> = cache::live_entries(&records)
+        .into_iter()
+        .filter(|entry| in_language(entry, languages, min_confidence))
+        .collect();
+    write_entries(out, &entries)
+}
+
+// Query filters, every one given has to match
+struct QueryArgs {
+    channel: Option<String>,
+    video: Option<String>,
+    title: Option<String>,
+    playlist: Option<String>,
+    kind: Option<Kind>,
+    stream: Option<StreamState>,
+    domain: Option<String>,
+    hashtag: Option<String>,
+    sponsored: Option<bool>,
+    languages: Vec<String>,
+    min_confidence: Option<u8>,
+}
+
+fn run_query(ctx: Context, cache: Option<PathBuf>, args: QueryArgs) -> Result<()> {
+    let QueryArgs {
+        channel,
+        video,
+        title,
+        playlist,
+        kind,
+        stream,
+        domain,
+        hashtag,
+        sponsored,
+        languages,
+        min_confidence,
+    } = args;
+    let hashtag = hashtag.map(|tag| tag.trim_start_matches('#').to_lowercase());
+    let overrides = KindOverrides::new(&ctx.config.kind);
+    let cleaner = TitleCleaner::new(&ctx.config.title);
+    let title = title.map(|title| cleaner.clean(&title).unwrap_or(title).to_lowercase());
+    let records = ctx.read_records(cache)?;
+    let positions = playlist.map(|playlist| cache::playlist_positions(&records, &playlist));
+    let mut entries: Vec<Entry> = cache::live_entries(&records)
+        .into_iter()
+        .filter(|entry| {
+            positions
+                .as_ref()
+                .is_none_or(|positions| positions.contains_key(&entry.video_id))
+        })
+        .filter(|entry| {
+            channel
+                .as_ref()
+                .is_none_or(|channel| &entry.channel_id == channel)
+        })
+        .filter(|entry| video.as_ref().is_none_or(|video| &entry.video_id == video))
+        .filter(|entry| {
+            title.as_ref().is_none_or(|title| {
+                [Some(&entry.title), entry.title_normalized.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .any(|text| text.to_lowercase().contains(title))
+            })
+        })
+        .filter(|entry| {
+            kind.is_none_or(|kind| overrides.get(&entry.channel_id).or(entry.kind) == Some(kind))
+        })
+        .filter(|entry| stream.is_none_or(|stream| entry.stream == Some(stream)))
+        .filter(|entry| {
+            domain
+                .as_ref()
+                .is_none_or(|domain| entry.links.iter().any(|link| link.is_to(domain)))
+        })
+        .filter(|entry| {
+            hashtag
+                .as_ref()
+                .is_none_or(|hashtag| entry.hashtags.contains(hashtag))
+        })
+        .filter(|entry| sponsored.is_none_or(|sponsored| entry.sponsored == sponsored))
+        .filter(|entry| in_language(entry, &languages, min_confidence))
+        .collect();
+    if let Some(positions) = &positions {
+        entries.sort_by_key(|entry| positions[&entry.video_id]);
+    }
+
+    write_entries(None, &entries)
+}
+
+// A channel linking to the domain, with how many of its videos do
+#[derive(Serialize)]
+struct Linking<'a> {
+    channel_id: &'a str,
+    author: &'a str,
+    videos: usize,
+}
+
+fn run_links(ctx: Context, cache: Option<PathBuf>, domain: &str) -> Result<()> {
+    let records = ctx.read_records(cache)?;
+    let entries = cache::live_entries(&records);
+    let mut channels: BTreeMap<&str, (&str, HashSet<&str>)> = BTreeMap::new();
+    for entry in &entries {
+        if entry.links.iter().any(|link| link.is_to(domain)) {
+            let (_, videos) = channels
+                .entry(&entry.channel_id)
+                .or_insert((&entry.author, HashSet::new()));
+            videos.insert(&entry.video_id);
+        }
+    }
+
+    let mut out = std::io::stdout().lock();
+    for (channel_id, (author, videos)) in channels {
+        let linking = Linking {
+            channel_id,
+            author,
+            videos: videos.len(),
+        };
+        serde_json::to_writer(&mut out, &linking)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+// Videos are counted once however many snapshots of them are cached
+#[derive(Default, Serialize)]
+struct SponsorReport<'a> {
+    channel_id: &'a str,
+    author: &'a str,
+    videos: usize,
+    // Videos a sponsor rule matched, named sponsor or not
+    sponsored: usize,
+    sponsors: BTreeMap<&'a str, usize>,
+}
+
+// Whether any snapshot of a video was sponsored, and every sponsor they named
+type VideoSponsors<'a> = (bool, BTreeSet<&'a str>);
+
+fn run_sponsors(ctx: Context, cache: Option<PathBuf>, channel: Option<&str>) -> Result<()> {
+    let records = ctx.read_records(cache)?;
+    let entries = cache::live_entries(&records);
+    let mut channels: BTreeMap<&str, (&str, BTreeMap<&str, VideoSponsors>)> = BTreeMap::new();
+    for entry in &entries {
+        if channel.is_some_and(|channel| entry.channel_id != channel) {
+            continue;
+        }
+        let (_, videos) = channels
+            .entry(&entry.channel_id)
+            .or_insert((&entry.author, BTreeMap::new()));
+        let (sponsored, sponsors) = videos.entry(&entry.video_id).or_default();
+        *sponsored |= entry.sponsored;
+        sponsors.extend(entry.sponsors.iter().map(String::as_str));
+    }
+
+    let mut out = std::io::stdout().lock();
+    for (channel_id, (author, videos)) in channels {
+        let mut report = SponsorReport {
+            channel_id,
+            author,
+            videos: videos.len(),
+            ..SponsorReport::default()
+        };
+        for (sponsored, sponsors) in videos.into_values() {
+            report.sponsored += usize::from(sponsored);
+            for sponsor in sponsors {
+                *report.sponsors.entry(sponsor).or_default() += 1;
+            }
+        }
+        serde_json::to_writer(&mut out, &report)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn run_streams(ctx: Context, cache: Option<PathBuf>, channel: Option<&str>) -> Result<()> {
+    let records = ctx.read_records(cache)?;
+    let entries: Vec<Entry> = cache::live_entries(&records)
+        .into_iter()
+        .filter(|entry| channel.is_none_or(|channel| entry.channel_id == channel))
+        .collect();
+
+    let mut out = std::io::stdout().lock();
+    for transition in stream::transitions(&entries) {
+        serde_json::to_writer(&mut out, &transition)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn run_verify(ctx: Context, cache: Option<PathBuf>) -> Result<()> {
+    let mut bad = 0;
+    let files = ctx.cache(cache)?.files()?;
+    for file in &files {
+        let problem = match std::fs::read(file) {
+            Err(e) => Some(e.to_string()),
+            Ok(bytes) => match cache::read_record(file) {
+                Err(e) => Some(e.to_string()),
+                Ok(_) => {
+                    let expected = cache::get_md5_hash(&bytes);
+                    let stem = file.file_stem().and_then(|stem| stem.to_str());
+                    (stem != Some(expected.as_str()))
+                        .then(|| format!("name does not match content, expected {}", expected))
+                }
+            },
+        };
+
+        if let Some(problem) = problem {
+            warn!(file = %file.display(), problem = %problem, "bad cache file");
+            bad += 1;
+        }
+    }
+
+    info!(files = files.len(), bad, "verified");
+    if bad > 0 {
+        return Err(format!("{} cache files failed verification", bad).into());
+    }
+    Ok(())
+}
+
+fn run_opml_import(ctx: Context, opml_path: &Path, list: Option<PathBuf>) -> Result<()> {
+    let list = list
+        .or_else(|| ctx.config.channels.lists.first().cloned())
+        .ok_or("no channel list, pass --channels or set channels.lists")?;
+    let xml = cache::get_file_string(opml_path)?;
+    let mut channels = opml::read_channel_list(&list)?;
+    let before = channels.len();
+    channels.extend(opml::parse_opml(&xml)?);
+    opml::write_channel_list(&list, &channels)?;
+
+    info!(
+        new = channels.len() - before,
+        total = channels.len(),
+        "imported channels"
+    );
+    Ok(())
+}
+
+fn run_opml_export(ctx: Context, cache: Option<PathBuf>, opml_path: &Path) -> Result<()> {
+    let channels = opml::cached_channels(&ctx.read_records(cache)?);
+    std::fs::write(opml_path, opml::render_opml(&channels))?;
+
+    info!(channels = channels.len(), "exported");
+    Ok(())
+}
+
+fn report_problems(problems: &[String]) -> Result<()> {
+    for problem in problems {
+        error!("config: {}", problem);
+    }
+    if !problems.is_empty() {
+        return Err(format!("{} problems in the configuration", problems.len()).into());
+    }
+    Ok(())
+}
+
+fn run_config_check(mut ctx: Context) -> Result<()> {
+    ctx.config.output.format = Some(ctx.config.format(ctx.default_format));
+    print!("{}", ctx.config.to_toml()?);
+    report_problems(&ctx.config.validate())
+}
+
+pub fn run(cli: Cli, default_format: Format) -> Result<()> {
+    let mut config = Config::load(cli.config.as_deref())?;
+    if let Some(level) = cli.log_level {
+        config.log.level = level;
+    }
+    if let Some(format) = cli.log_format {
+        config.log.format = format;
+    }
+    if let Some(threads) = cli.threads {
+        config.threads = threads;
+    }
+    if let Some(bytes) = cli.max_inflight_bytes {
+        config.max_inflight_bytes = bytes;
+    }
+    // A bad level is reported by validation, log at the default until then
+    if log::init(&config.log.level, config.log.format).is_err() {
+        log::init(&LogConfig::default().level, config.log.format)?;
+    }
+
+    let ctx = Context {
+        config,
+        default_format,
+    };
+    if let Command::Config(ConfigCommand::Check) = cli.command {
+        return run_config_check(ctx);
+    }
+
+    report_problems(&ctx.config.validate())?;
+    match cli.command {
+        Command::Parse {
+            src,
+            dst,
+            format,
+            full,
+            include,
+            exclude,
+            symlinks,
+            progress,
+            incomplete,
+            quarantine,
+        } => run_parse(
+            ctx,
+            ParseArgs {
+                src,
+                dst,
+                format,
+                full,
+                include,
+                exclude,
+                symlinks,
+                progress,
+                incomplete,
+                quarantine,
+            },
+        ),
+        Command::Watch {
+            src,
+            dst,
+            format,
+            processed,
+            failed,
+        } => run_watch(
+            ctx,
+            WatchArgs {
+                src,
+                dst,
+                format,
+                processed,
+                failed,
+            },
+        ),
+        Command::Fetch { channels, dst } => run_fetch(ctx, channels, dst),
+        Command::Export {
+            cache,
+            out,
+            language,
+            min_confidence,
+        } => run_export(ctx, cache, out.as_deref(), &language, min_confidence),
+        Command::Query {
+            cache,
+            channel,
+            video,
+            title,
+            playlist,
+            kind,
+            stream,
+            domain,
+            hashtag,
+            sponsored,
+            language,
+            min_confidence,
+        } => run_query(
+            ctx,
+            cache,
+            QueryArgs {
+                channel,
+                video,
+                title,
+                playlist,
+                kind,
+                stream,
+                domain,
+                hashtag,
+                sponsored,
+                languages: language,
+                min_confidence,
+            },
+        ),
+        Command::Sponsors { cache, channel } => run_sponsors(ctx, cache, channel.as_deref()),
+        Command::Links { cache, domain } => run_links(ctx, cache, &domain),
+        Command::Streams { cache, channel } => run_streams(ctx, cache, channel.as_deref()),
+        Command::Verify { cache } => run_verify(ctx, cache),
+        Command::Opml(OpmlCommand::Import { opml, channels }) => {
+            run_opml_import(ctx, &opml, channels)
+        }
+        Command::Opml(OpmlCommand::Export { cache, opml }) => run_opml_export(ctx, cache, &opml),
+        Command::Config(ConfigCommand::Check) => {
+            unreachable!("config check runs before validation")
+        }
+    }
+}
+
+// Entry point for the binaries: usage errors exit 2, failed runs exit 1
+pub fn main(default_format: Format) -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli, default_format) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // Errors from before logging was set up still need to reach the user
+            if tracing::dispatcher::has_been_set() {
+                error!("{}", e);
+            } else {
+                eprintln!("error: {}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stdio_is_a_parse_src_and_dst() {
+        let cli =
+            Cli::try_parse_from(["youtube-json", "parse", "--src", "-", "--dst", "-"]).unwrap();
+        match cli.command {
+            Command::Parse { src, dst, .. } => {
+                assert_eq!(src, [PathBuf::from("-")]);
+                assert_eq!(dst, Some(PathBuf::from("-")));
+            }
+            command => panic!("parsed as {:?}", command),
+        }
+
+        assert!(Cli::try_parse_from(["youtube-json", "-", "-"]).is_err());
+        assert!(Cli::try_parse_from(["youtube-json"]).is_err());
+    }
+
+    #[test]
+    fn global_flags_before_the_subcommand() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("c.toml");
+        std::fs::write(&config, "").unwrap();
+        let config = config.to_str().unwrap();
+
+        let cli =
+            Cli::try_parse_from(["youtube-json", "--config", config, "config", "check"]).unwrap();
+        assert_eq!(cli.config.as_deref(), Some(Path::new(config)));
+        assert!(matches!(cli.command, Command::Config(ConfigCommand::Check)));
+
+        let cli = Cli::try_parse_from([
+            "youtube-json",
+            "--log-format",
+            "json",
+            "parse",
+            "--src",
+            "-",
+        ])
+        .unwrap();
+        assert_eq!(cli.log_format, Some(LogFormat::Json));
+        assert!(matches!(cli.command, Command::Parse { .. }));
+
+        let cli = Cli::try_parse_from(["youtube-json", "--threads", "2", "verify"]).unwrap();
+        assert_eq!(cli.threads, Some(2));
+        assert!(matches!(cli.command, Command::Verify { .. }));
+
+        let cli = Cli::try_parse_from(["youtube-json", "verify", "--config", config]).unwrap();
+        assert_eq!(cli.config.as_deref(), Some(Path::new(config)));
+        assert!(matches!(cli.command, Command::Verify { .. }));
+    }
+}
@@ -0,0 +1,623 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Settings from youtube-rss-cached.toml, overridden by YOUTUBE_RSS_* env vars
+ *
+*/
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::discover::{glob_set, SymlinkPolicy};
+use crate::format::Format;
+use crate::ingest::IncompletePolicy;
+use crate::language;
+use crate::log::LogFormat;
+use crate::progress::ProgressMode;
+use crate::sponsor;
+use crate::title::NormalForm;
+use crate::Result;
+
+pub const DEFAULT_PATH: &str = "youtube-rss-cached.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // Size of the rayon pool used by parse and watch, defaults to the available cores
+    pub threads: usize,
+    // Upper bound on the on-disk size of feed files held in memory at once; a
+    // compressed file or archive takes more than its size once decompressed
+    pub max_inflight_bytes: u64,
+    pub input: InputConfig,
+    pub output: OutputConfig,
+    pub fetch: FetchConfig,
+    pub channels: ChannelsConfig,
+    pub watch: WatchConfig,
+    pub kind: KindConfig,
+    pub stream: StreamConfig,
+    pub sponsor: SponsorConfig,
+    pub language: LanguageConfig,
+    pub title: TitleConfig,
+    pub log: LogConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputConfig {
+    // Directories of feed xml files read by parse, walked recursively
+    pub dirs: Vec<PathBuf>,
+    // Globs relative to an input directory; a file must match an include and no exclude
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub symlinks: SymlinkPolicy,
+    // Record of ingested files, defaults to ingest.manifest in the cache directory
+    pub manifest: Option<PathBuf>,
+    // Entries missing fields are skipped, written with nulls or quarantined
+    pub incomplete: IncompletePolicy,
+    // Directory quarantined entries are saved to as raw xml
+    pub quarantine: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    // Cache directory parse writes to and export, query and verify read from
+    pub dir: Option<PathBuf>,
+    // Record encoding, the binary decides when unset
+    pub format: Option<Format>,
+    // Leading md5 characters used as a cache subdirectory, 0 keeps the cache flat
+    pub shard_chars: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FetchConfig {
+    // Directory fetched feed xml is saved into
+    pub dst: Option<PathBuf>,
+    pub timeout_secs: u64,
+    pub user_agent: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChannelsConfig {
+    // Channel list files, one channel id per line
+    pub lists: Vec<PathBuf>,
+    // Channel ids listed directly in the config
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchConfig {
+    // Spool files that parsed are moved here, kept in place when unset
+    pub processed: Option<PathBuf>,
+    // Spool files that failed to parse are moved here, kept in place when unset
+    pub failed: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KindConfig {
+    // Channel ids whose uploads are all Shorts, or all regular videos,
+    // whatever the feed hints say
+    pub shorts: Vec<String>,
+    pub videos: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StreamConfig {
+    // Title words marking a live stream or premiere, matched ignoring case
+    pub markers: Vec<String>,
+    // A marked entry updated longer than this after it was published has ended
+    pub window_secs: u64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> StreamConfig {
+        StreamConfig {
+            markers: ["live", "livestream", "live stream", "premiere", "🔴"]
+                .map(String::from)
+                .to_vec(),
+            window_secs: 12 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SponsorConfig {
+    // Phrases marking paid promotion, matched as whole words ignoring case
+    pub keywords: Vec<String>,
+    // Regexes over title and description; the first capture group names the sponsor.
+    // Only set in the file, a list of regexes does not split on commas.
+    pub patterns: Vec<String>,
+    // Domains of known sponsors, found among the description links
+    pub domains: Vec<String>,
+}
+
+impl Default for SponsorConfig {
+    fn default() -> SponsorConfig {
+        // Sponsor names are the capitalised words around the phrase
+        let name = r"([A-Z][\w&.'-]*(?: [A-Z][\w&.'-]*)*)";
+        SponsorConfig {
+            keywords: [
+                "sponsored by",
+                "paid promotion",
+                "promo code",
+                "use code",
+                "#ad",
+                "#sponsored",
+            ]
+            .map(String::from)
+            .to_vec(),
+            patterns: vec![
+                format!(r"(?i:thanks to) {} (?i:for sponsoring)", name),
+                format!(r"(?i:sponsored by) {}", name),
+            ],
+            domains: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LanguageConfig {
+    // ISO 639-3 codes detection picks from, every language it knows when empty
+    pub languages: Vec<String>,
+    // Confidence from 0 to 100 a language needs to be recorded; when unset the
+    // detector's own idea of a reliable guess decides
+    pub min_confidence: Option<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TitleConfig {
+    // Unicode form title_normalized is written in, nfc or nfkc
+    pub normalize: Option<NormalForm>,
+    // Leave control and zero width characters out of title_normalized
+    pub strip_controls: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    // Env filter directive, e.g. "info" or "youtube_rss_cached=debug"
+    pub level: String,
+    pub format: LogFormat,
+    // Progress of a parse run: auto, bar, log or off
+    pub progress: ProgressMode,
+    // Seconds between progress log lines when there is no terminal to draw on
+    pub progress_secs: u64,
+}
+
+impl Default for LogConfig {
+    fn default() -> LogConfig {
+        LogConfig {
+            level: "info".to_string(),
+            format: LogFormat::default(),
+            progress: ProgressMode::default(),
+            progress_secs: 10,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            max_inflight_bytes: 256 * 1024 * 1024,
+            input: InputConfig::default(),
+            output: OutputConfig::default(),
+            fetch: FetchConfig::default(),
+            channels: ChannelsConfig::default(),
+            watch: WatchConfig::default(),
+            kind: KindConfig::default(),
+            stream: StreamConfig::default(),
+            sponsor: SponsorConfig::default(),
+            language: LanguageConfig::default(),
+            title: TitleConfig::default(),
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl Default for InputConfig {
+    fn default() -> InputConfig {
+        InputConfig {
+            dirs: Vec::new(),
+            include: [
+                "**/*.xml",
+                "**/*.xml.gz",
+                "**/*.xml.zst",
+                "**/*.tar",
+                "**/*.tar.gz",
+                "**/*.tgz",
+                "**/*.tar.zst",
+                "**/*.tzst",
+            ]
+            .iter()
+            .map(|glob| glob.to_string())
+            .collect(),
+            exclude: Vec::new(),
+            symlinks: SymlinkPolicy::default(),
+            manifest: None,
+            incomplete: IncompletePolicy::default(),
+            quarantine: None,
+        }
+    }
+}
+
+impl Default for FetchConfig {
+    fn default() -> FetchConfig {
+        FetchConfig {
+            dst: None,
+            timeout_secs: 30,
+            user_agent: concat!("youtube-rss-cached/", env!("CARGO_PKG_VERSION")).to_string(),
+        }
+    }
+}
+
+fn env_var(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.is_empty())
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Result<Option<T>>
+where
+    T::Err: std::fmt::Display,
+{
+    match env_var(key) {
+        Some(value) => match value.parse() {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => Err(format!("{}={}: {}", key, value, e).into()),
+        },
+        None => Ok(None),
+    }
+}
+
+fn env_list(key: &str) -> Option<Vec<String>> {
+    env_var(key).map(|value| {
+        value
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .collect()
+    })
+}
+
+fn env_paths(key: &str) -> Option<Vec<PathBuf>> {
+    env_var(key).map(|value| std::env::split_paths(&value).collect())
+}
+
+impl Config {
+    // Load the config file, if any, then apply env overrides.
+    // An explicit path must exist; the default path is optional.
+    pub fn load(path: Option<&Path>) -> Result<Config> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| env_var("YOUTUBE_RSS_CONFIG").map(PathBuf::from));
+        let mut config = match path {
+            Some(path) => Config::from_file(&path)?,
+            None if Path::new(DEFAULT_PATH).is_file() => {
+                Config::from_file(Path::new(DEFAULT_PATH))?
+            }
+            None => Config::default(),
+        };
+
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Config> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("reading {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    pub fn apply_env(&mut self) -> Result<()> {
+        if let Some(threads) = env_parse("YOUTUBE_RSS_THREADS")? {
+            self.threads = threads;
+        }
+        if let Some(bytes) = env_parse("YOUTUBE_RSS_MAX_INFLIGHT_BYTES")? {
+            self.max_inflight_bytes = bytes;
+        }
+        if let Some(dirs) = env_paths("YOUTUBE_RSS_INPUT_DIRS") {
+            self.input.dirs = dirs;
+        }
+        if let Some(include) = env_list("YOUTUBE_RSS_INPUT_INCLUDE") {
+            self.input.include = include;
+        }
+        if let Some(exclude) = env_list("YOUTUBE_RSS_INPUT_EXCLUDE") {
+            self.input.exclude = exclude;
+        }
+        if let Some(symlinks) = env_parse("YOUTUBE_RSS_INPUT_SYMLINKS")? {
+            self.input.symlinks = symlinks;
+        }
+        if let Some(manifest) = env_var("YOUTUBE_RSS_INPUT_MANIFEST") {
+            self.input.manifest = Some(PathBuf::from(manifest));
+        }
+        if let Some(incomplete) = env_parse("YOUTUBE_RSS_INPUT_INCOMPLETE")? {
+            self.input.incomplete = incomplete;
+        }
+        if let Some(dir) = env_var("YOUTUBE_RSS_INPUT_QUARANTINE") {
+            self.input.quarantine = Some(PathBuf::from(dir));
+        }
+        if let Some(dir) = env_var("YOUTUBE_RSS_OUTPUT_DIR") {
+            self.output.dir = Some(PathBuf::from(dir));
+        }
+        if let Some(format) = env_parse("YOUTUBE_RSS_OUTPUT_FORMAT")? {
+            self.output.format = Some(format);
+        }
+        if let Some(shard_chars) = env_parse("YOUTUBE_RSS_OUTPUT_SHARD_CHARS")? {
+            self.output.shard_chars = shard_chars;
+        }
+        if let Some(dst) = env_var("YOUTUBE_RSS_FETCH_DST") {
+            self.fetch.dst = Some(PathBuf::from(dst));
+        }
+        if let Some(timeout_secs) = env_parse("YOUTUBE_RSS_FETCH_TIMEOUT_SECS")? {
+            self.fetch.timeout_secs = timeout_secs;
+        }
+        if let Some(user_agent) = env_var("YOUTUBE_RSS_FETCH_USER_AGENT") {
+            self.fetch.user_agent = user_agent;
+        }
+        if let Some(lists) = env_paths("YOUTUBE_RSS_CHANNEL_LISTS") {
+            self.channels.lists = lists;
+        }
+        if let Some(ids) = env_list("YOUTUBE_RSS_CHANNEL_IDS") {
+            self.channels.ids = ids;
+        }
+        if let Some(processed) = env_var("YOUTUBE_RSS_WATCH_PROCESSED") {
+            self.watch.processed = Some(PathBuf::from(processed));
+        }
+        if let Some(failed) = env_var("YOUTUBE_RSS_WATCH_FAILED") {
+            self.watch.failed = Some(PathBuf::from(failed));
+        }
+        if let Some(shorts) = env_list("YOUTUBE_RSS_KIND_SHORTS") {
+            self.kind.shorts = shorts;
+        }
+        if let Some(videos) = env_list("YOUTUBE_RSS_KIND_VIDEOS") {
+            self.kind.videos = videos;
+        }
+        if let Some(markers) = env_list("YOUTUBE_RSS_STREAM_MARKERS") {
+            self.stream.markers = markers;
+        }
+        if let Some(window_secs) = env_parse("YOUTUBE_RSS_STREAM_WINDOW_SECS")? {
+            self.stream.window_secs = window_secs;
+        }
+        if let Some(keywords) = env_list("YOUTUBE_RSS_SPONSOR_KEYWORDS") {
+            self.sponsor.keywords = keywords;
+        }
+        if let Some(domains) = env_list("YOUTUBE_RSS_SPONSOR_DOMAINS") {
+            self.sponsor.domains = domains;
+        }
+        if let Some(languages) = env_list("YOUTUBE_RSS_LANGUAGE_LANGUAGES") {
+            self.language.languages = languages;
+        }
+        if let Some(min_confidence) = env_parse("YOUTUBE_RSS_LANGUAGE_MIN_CONFIDENCE")? {
+            self.language.min_confidence = Some(min_confidence);
+        }
+        if let Some(normalize) = env_parse("YOUTUBE_RSS_TITLE_NORMALIZE")? {
+            self.title.normalize = Some(normalize);
+        }
+        if let Some(strip_controls) = env_parse("YOUTUBE_RSS_TITLE_STRIP_CONTROLS")? {
+            self.title.strip_controls = strip_controls;
+        }
+        if let Some(level) = env_var("YOUTUBE_RSS_LOG_LEVEL") {
+            self.log.level = level;
+        }
+        if let Some(format) = env_parse("YOUTUBE_RSS_LOG_FORMAT")? {
+            self.log.format = format;
+        }
+        if let Some(progress) = env_parse("YOUTUBE_RSS_LOG_PROGRESS")? {
+            self.log.progress = progress;
+        }
+        if let Some(secs) = env_parse("YOUTUBE_RSS_LOG_PROGRESS_SECS")? {
+            self.log.progress_secs = secs;
+        }
+
+        Ok(())
+    }
+
+    // Every problem with the settings as `key: message`, empty when they are usable
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.threads == 0 {
+            problems.push("threads: must be at least 1".to_string());
+        }
+        if self.max_inflight_bytes == 0 {
+            problems.push("max_inflight_bytes: must be at least 1".to_string());
+        }
+        for dir in &self.input.dirs {
+            if !dir.is_dir() {
+                problems.push(format!("input.dirs: {} is not a directory", dir.display()));
+            }
+        }
+        if self.input.include.is_empty() {
+            problems.push("input.include: must have at least one glob".to_string());
+        }
+        if let Err(e) = glob_set(&self.input.include) {
+            problems.push(format!("input.include: {}", e));
+        }
+        if let Err(e) = glob_set(&self.input.exclude) {
+            problems.push(format!("input.exclude: {}", e));
+        }
+        match &self.input.quarantine {
+            Some(dir) if !dir.is_dir() => problems.push(format!(
+                "input.quarantine: {} is not a directory",
+                dir.display()
+            )),
+            None if self.input.incomplete == IncompletePolicy::Quarantine => {
+                problems.push("input.quarantine: must be set to quarantine entries".to_string())
+            }
+            _ => (),
+        }
+        if let Some(dir) = &self.output.dir {
+            if !dir.is_dir() {
+                problems.push(format!("output.dir: {} is not a directory", dir.display()));
+            }
+        }
+        if self.output.shard_chars > 32 {
+            problems
+                .push("output.shard_chars: can be at most 32, the length of an md5".to_string());
+        }
+        if let Some(dst) = &self.fetch.dst {
+            if !dst.is_dir() {
+                problems.push(format!("fetch.dst: {} is not a directory", dst.display()));
+            }
+        }
+        if self.fetch.timeout_secs == 0 {
+            problems.push("fetch.timeout_secs: must be at least 1".to_string());
+        }
+        for list in &self.channels.lists {
+            if !list.is_file() {
+                problems.push(format!("channels.lists: {} is not a file", list.display()));
+            }
+        }
+        for id in &self.channels.ids {
+            if id.is_empty() || id.contains(|c: char| c.is_whitespace() || c == '/') {
+                problems.push(format!("channels.ids: {:?} is not a channel id", id));
+            }
+        }
+        for (key, dir) in [
+            ("watch.processed", &self.watch.processed),
+            ("watch.failed", &self.watch.failed),
+        ] {
+            if let Some(dir) = dir {
+                if !dir.is_dir() {
+                    problems.push(format!("{}: {} is not a directory", key, dir.display()));
+                }
+            }
+        }
+        for id in &self.kind.shorts {
+            if self.kind.videos.contains(id) {
+                problems.push(format!("kind: {} is in both shorts and videos", id));
+            }
+        }
+        if self
+            .stream
+            .markers
+            .iter()
+            .any(|marker| marker.trim().is_empty())
+        {
+            problems.push("stream.markers: markers must not be empty".to_string());
+        }
+        if self.stream.window_secs == 0 {
+            problems.push("stream.window_secs: must be at least 1".to_string());
+        }
+        for pattern in &self.sponsor.patterns {
+            if let Err(e) = sponsor::compile(pattern) {
+                problems.push(format!("sponsor.patterns: {}", e));
+            }
+        }
+        if self.sponsor.keywords.iter().any(|k| k.trim().is_empty()) {
+            problems.push("sponsor.keywords: keywords must not be empty".to_string());
+        }
+        for code in &self.language.languages {
+            if language::lang(code).is_none() {
+                problems.push(format!(
+                    "language.languages: {:?} is not an ISO 639-3 code detection knows",
+                    code
+                ));
+            }
+        }
+        if self.language.min_confidence.is_some_and(|min| min > 100) {
+            problems.push("language.min_confidence: can be at most 100".to_string());
+        }
+        if let Err(e) = tracing_subscriber::EnvFilter::try_new(&self.log.level) {
+            problems.push(format!("log.level: {}", e));
+        }
+        if self.log.progress_secs == 0 {
+            problems.push("log.progress_secs: must be at least 1".to_string());
+        }
+
+        problems
+    }
+
+    pub fn format(&self, default: Format) -> Format {
+        self.output.format.unwrap_or(default)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_fills_in_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+threads = 4
+
+[output]
+format = "capnp"
+shard_chars = 2
+
+[channels]
+ids = ["UCRijo3ddMTht_IHyNSNXpNQ"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.threads, 4);
+        assert_eq!(config.output.format, Some(Format::Capnp));
+        assert_eq!(config.output.shard_chars, 2);
+        assert_eq!(config.channels.ids, ["UCRijo3ddMTht_IHyNSNXpNQ"]);
+        assert_eq!(config.fetch, FetchConfig::default());
+
+        assert!(toml::from_str::<Config>("thread = 4").is_err());
+    }
+
+    #[test]
+    fn problems_are_keyed() {
+        let mut config = Config::default();
+        assert!(config.validate().is_empty());
+
+        config.threads = 0;
+        config.max_inflight_bytes = 0;
+        config.output.shard_chars = 33;
+        config.fetch.timeout_secs = 0;
+        config.input.include = Vec::new();
+        config.input.exclude = vec!["[a-".to_string()];
+        config.input.incomplete = IncompletePolicy::Quarantine;
+        config.channels.ids = vec!["not a channel".to_string()];
+        let problems = config.validate();
+        assert!(problems[3].starts_with("input.exclude: [a-: "));
+        assert_eq!(
+            problems,
+            [
+                "threads: must be at least 1",
+                "max_inflight_bytes: must be at least 1",
+                "input.include: must have at least one glob",
+                &problems[3],
+                "input.quarantine: must be set to quarantine entries",
+                "output.shard_chars: can be at most 32, the length of an md5",
+                "fetch.timeout_secs: must be at least 1",
+                "channels.ids: \"not a channel\" is not a channel id",
+            ]
+        );
+    }
+
+    // The only test touching YOUTUBE_RSS_* so tests running in parallel don't see it
+    #[test]
+    fn env_overrides_the_file() {
+        std::env::set_var("YOUTUBE_RSS_OUTPUT_SHARD_CHARS", "3");
+        std::env::set_var("YOUTUBE_RSS_CHANNEL_IDS", "UCa, UCb");
+        let mut config = Config::default();
+        let applied = config.apply_env();
+        std::env::set_var("YOUTUBE_RSS_OUTPUT_SHARD_CHARS", "three");
+        let bad = Config::default().apply_env();
+        std::env::remove_var("YOUTUBE_RSS_OUTPUT_SHARD_CHARS");
+        std::env::remove_var("YOUTUBE_RSS_CHANNEL_IDS");
+
+        applied.unwrap();
+        assert_eq!(config.output.shard_chars, 3);
+        assert_eq!(config.channels.ids, ["UCa", "UCb"]);
+        assert!(bad
+            .unwrap_err()
+            .to_string()
+            .starts_with("YOUTUBE_RSS_OUTPUT_SHARD_CHARS=three: "));
+    }
+}
@@ -0,0 +1,172 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Finds feed files under the input directories
+ *
+*/
+
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use walkdir::WalkDir;
+
+use crate::config::InputConfig;
+use crate::Result;
+
+// What to do with symbolic links met while walking an input directory
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkPolicy {
+    // Ignore every link
+    Skip,
+    // Read links that point at files, never descend through linked directories
+    #[default]
+    Files,
+    // Follow links to files and directories, loops are reported and skipped
+    Follow,
+}
+
+impl std::str::FromStr for SymlinkPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<SymlinkPolicy, String> {
+        <SymlinkPolicy as ValueEnum>::from_str(s, true)
+    }
+}
+
+pub struct Discovery {
+    include: GlobSet,
+    exclude: GlobSet,
+    symlinks: SymlinkPolicy,
+}
+
+// A bad pattern is named in the error, config check reports it per list
+pub fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).map_err(|e| format!("{}: {}", pattern, e))?);
+    }
+
+    Ok(builder.build()?)
+}
+
+impl Discovery {
+    pub fn new(config: &InputConfig) -> Result<Discovery> {
+        Ok(Discovery {
+            include: glob_set(&config.include)?,
+            exclude: glob_set(&config.exclude)?,
+            symlinks: config.symlinks,
+        })
+    }
+
+    // Globs are matched against the path relative to the input directory
+    pub fn wanted(&self, relative: &Path) -> bool {
+        self.include.is_match(relative) && !self.exclude.is_match(relative)
+    }
+
+    // Every matching file under root, sorted by path
+    pub fn files(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        let walker = WalkDir::new(root)
+            .follow_links(self.symlinks == SymlinkPolicy::Follow)
+            .sort_by_file_name();
+
+        let mut files = Vec::new();
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) if e.loop_ancestor().is_some() => {
+                    warn!(error = %e, "skipping symlink loop");
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let is_file = match (entry.path_is_symlink(), self.symlinks) {
+                (true, SymlinkPolicy::Skip) => false,
+                (true, SymlinkPolicy::Files) => entry.path().is_file(),
+                _ => entry.file_type().is_file(),
+            };
+            if !is_file {
+                continue;
+            }
+
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            if self.wanted(relative) {
+                files.push(entry.into_path());
+            }
+        }
+
+        Ok(files)
+    }
+
+    // Files from every root, sorted and without duplicates when roots overlap
+    pub fn all_files(&self, roots: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for root in roots {
+            files.extend(self.files(root)?);
+        }
+
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(root: &Path, relative: &str) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "<feed/>").unwrap();
+    }
+
+    fn relative_files(discovery: &Discovery, root: &Path) -> Vec<String> {
+        discovery
+            .files(root)
+            .unwrap()
+            .iter()
+            .map(|file| file.strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn include_and_exclude_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        touch(dir.path(), "a.xml");
+        touch(dir.path(), "notes.txt");
+        touch(dir.path(), "2022/b.xml");
+        touch(dir.path(), "2022/drafts/c.xml");
+
+        let mut config = InputConfig::default();
+        let discovery = Discovery::new(&config).unwrap();
+        assert_eq!(
+            relative_files(&discovery, dir.path()),
+            ["2022/b.xml", "2022/drafts/c.xml", "a.xml"]
+        );
+
+        config.exclude = vec!["**/drafts/**".to_string()];
+        let discovery = Discovery::new(&config).unwrap();
+        assert_eq!(
+            relative_files(&discovery, dir.path()),
+            ["2022/b.xml", "a.xml"]
+        );
+
+        config.include = vec!["*.txt".to_string(), "a.*".to_string()];
+        config.exclude = Vec::new();
+        let discovery = Discovery::new(&config).unwrap();
+        assert_eq!(
+            relative_files(&discovery, dir.path()),
+            ["a.xml", "notes.txt"]
+        );
+    }
+
+    #[test]
+    fn bad_glob_is_named() {
+        let e = glob_set(&["**/*.xml".to_string(), "[a-".to_string()]).unwrap_err();
+        assert!(e.to_string().starts_with("[a-: "));
+    }
+}
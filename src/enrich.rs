@@ -0,0 +1,402 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Links, hashtags, mentions and chapters pulled out of entry descriptions
+ *
+*/
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Link {
+    pub url: String,
+    // Host of the url in lowercase, without a leading www.
+    pub domain: String,
+}
+
+impl Link {
+    // None for urls without a host that has a dot in it
+    pub fn new(url: &str) -> Option<Link> {
+        let (_, rest) = url.split_once("://")?;
+        let host = rest.split(['/', '?', '#']).next()?;
+        let host = host.rsplit('@').next()?;
+        let host = host.split(':').next()?.to_lowercase();
+        let domain = host.strip_prefix("www.").unwrap_or(&host);
+        if !domain.contains('.') || domain.starts_with('.') || domain.ends_with('.') {
+            return None;
+        }
+
+        Some(Link {
+            url: url.to_string(),
+            domain: domain.to_string(),
+        })
+    }
+
+    // The domain itself or any of its subdomains
+    pub fn is_to(&self, domain: &str) -> bool {
+        let domain = domain.trim().to_lowercase();
+        let domain = domain.strip_prefix("www.").unwrap_or(&domain);
+        self.domain == domain
+            || self
+                .domain
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.'))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+    // Offset into the video
+    pub start_secs: u32,
+    pub title: String,
+}
+
+// YouTube only shows chapters when there are this many
+const MIN_CHAPTERS: usize = 3;
+// and each one lasts at least this long
+const MIN_CHAPTER_SECS: u32 = 10;
+
+// What a description yields; links, hashtags and mentions in order of first
+// appearance without repeats
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Extracted {
+    pub links: Vec<Link>,
+    // Lowercased and without the # or @
+    pub hashtags: Vec<String>,
+    pub mentions: Vec<String>,
+    pub chapters: Vec<Chapter>,
+}
+
+// Descriptions are cached with their xml escapes; text is looked at unescaped so
+// a url keeps its & between parameters. Broken escapes are left as they are.
+pub fn unescape(description: &str) -> Cow<'_, str> {
+    quick_xml::escape::unescape(description).unwrap_or(Cow::Borrowed(description))
+}
+
+// Word found with no letter or digit right before or after it, so "live" does
+// not match "delivery"
+pub fn contains_word(text: &str, word: &str) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+    text.match_indices(word).any(|(at, _)| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+pub fn extract(description: Option<&str>) -> Extracted {
+    let Some(description) = description else {
+        return Extracted::default();
+    };
+    let text = unescape(description);
+
+    let mut extracted = Extracted::default();
+    // Hashtags and mentions are looked for outside urls, so a #fragment is not a tag
+    let mut rest = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end) in url_spans(&text) {
+        if let Some(link) = Link::new(&text[start..end]) {
+            if !extracted.links.contains(&link) {
+                extracted.links.push(link);
+            }
+        }
+        rest.push_str(&text[last..start]);
+        rest.push(' ');
+        last = end;
+    }
+    rest.push_str(&text[last..]);
+
+    for tag in words_after(&rest, '#', |c| c.is_alphanumeric() || c == '_') {
+        if tag.chars().any(char::is_alphabetic) {
+            push_new(&mut extracted.hashtags, tag.to_lowercase());
+        }
+    }
+    for handle in words_after(&rest, '@', |c| {
+        c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+    }) {
+        let handle = handle.trim_end_matches(['.', '-']);
+        if !handle.is_empty() {
+            push_new(&mut extracted.mentions, handle.to_lowercase());
+        }
+    }
+
+    extracted.chapters = chapters(&text);
+    extracted
+}
+
+// Chapters are the lines carrying a timestamp from the first one at 0:00 on, and
+// only count when they follow youtube's rules: at least three, in ascending
+// order, ten seconds apart or more. Anything else gives no chapters at all.
+fn chapters(text: &str) -> Vec<Chapter> {
+    let chapters: Vec<Chapter> = text
+        .lines()
+        .filter_map(chapter_line)
+        .skip_while(|chapter| chapter.start_secs != 0)
+        .collect();
+    let ascending = chapters
+        .windows(2)
+        .all(|pair| pair[1].start_secs >= pair[0].start_secs + MIN_CHAPTER_SECS);
+
+    match chapters.len() >= MIN_CHAPTERS && ascending {
+        true => chapters,
+        false => Vec::new(),
+    }
+}
+
+// A line like "3:15 Topic", "Topic - 1:03:15" or "(0:00) Intro"; the title is
+// what is left of the line around the first timestamp
+fn chapter_line(line: &str) -> Option<Chapter> {
+    let (at, len, start_secs) = line.char_indices().find_map(|(at, c)| {
+        let boundary = !line[..at]
+            .chars()
+            .next_back()
+            .is_some_and(|p| p.is_ascii_digit() || p == ':');
+        if !boundary || !c.is_ascii_digit() {
+            return None;
+        }
+        clock(&line[at..]).map(|(len, secs)| (at, len, secs))
+    })?;
+
+    let separators = |c: char| c.is_whitespace() || "-–—|:•·()[]".contains(c);
+    let before = line[..at].trim_matches(separators);
+    let after = line[at + len..].trim_matches(separators);
+    let title = match (before.is_empty(), after.is_empty()) {
+        (true, true) => return None,
+        (false, true) => before.to_string(),
+        (true, false) => after.to_string(),
+        (false, false) => format!("{} {}", before, after),
+    };
+
+    Some(Chapter { start_secs, title })
+}
+
+// M:SS, MM:SS or H:MM:SS at the start of the text, as its length and seconds
+fn clock(text: &str) -> Option<(usize, u32)> {
+    let len = text
+        .find(|c: char| !c.is_ascii_digit() && c != ':')
+        .unwrap_or(text.len());
+    let token = text[..len].trim_end_matches(':');
+    let parts: Vec<&str> = token.split(':').collect();
+    if !(2..=3).contains(&parts.len()) || parts[0].is_empty() || parts[0].len() > 2 {
+        return None;
+    }
+    if parts[1..].iter().any(|part| part.len() != 2) {
+        return None;
+    }
+
+    let mut secs = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let value: u32 = part.parse().ok()?;
+        if i > 0 && value >= 60 {
+            return None;
+        }
+        secs = secs * 60 + value;
+    }
+    Some((token.len(), secs))
+}
+
+fn push_new(list: &mut Vec<String>, item: String) {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+// Byte ranges of http and https urls, running up to whitespace, a quote or an
+// angle bracket, less trailing punctuation and unbalanced closing brackets
+fn url_spans(text: &str) -> Vec<(usize, usize)> {
+    let lower = text.to_ascii_lowercase();
+    let mut spans = Vec::new();
+    let mut from = 0;
+    while let Some(found) = lower[from..].find("http") {
+        let start = from + found;
+        let scheme = ["https://", "http://"]
+            .iter()
+            .find(|scheme| lower[start..].starts_with(*scheme));
+        let after_word = text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        let Some(scheme) = scheme.filter(|_| !after_word) else {
+            from = start + 4;
+            continue;
+        };
+
+        let mut end = text[start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\''))
+            .map_or(text.len(), |len| start + len);
+        loop {
+            let url = &text[start..end];
+            let trim = match url.chars().next_back() {
+                Some('.' | ',' | ';' | ':' | '!' | '?') => true,
+                Some(')') => url.matches('(').count() < url.matches(')').count(),
+                Some(']') => url.matches('[').count() < url.matches(']').count(),
+                _ => false,
+            };
+            if !trim {
+                break;
+            }
+            end -= 1;
+        }
+
+        if end > start + scheme.len() {
+            spans.push((start, end));
+        }
+        from = end.max(start + scheme.len());
+    }
+
+    spans
+}
+
+// Words right after a marker character that does not follow a letter or digit,
+// which keeps email addresses out of mentions
+fn words_after(text: &str, marker: char, word: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut previous = None;
+    for (at, c) in text.char_indices() {
+        if c == marker && !previous.is_some_and(|p: char| p.is_alphanumeric() || p == marker) {
+            let start = at + c.len_utf8();
+            let len = text[start..]
+                .find(|c: char| !word(c))
+                .unwrap_or(text.len() - start);
+            if len > 0 {
+                words.push(&text[start..start + len]);
+            }
+        }
+        previous = Some(c);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::entries;
+
+    fn chapter(start_secs: u32, title: &str) -> Chapter {
+        Chapter {
+            start_secs,
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn fixture_chapters() {
+        let chapters: Vec<(String, Vec<Chapter>)> =
+            entries(include_str!("../data/src/chapters.xml"))
+                .into_iter()
+                .map(|entry| {
+                    let chapters = extract(entry.description.as_deref()).chapters;
+                    (entry.video_id, chapters)
+                })
+                .collect();
+
+        assert_eq!(
+            chapters[0],
+            (
+                "Ch4ptersOk1".to_string(),
+                vec![
+                    chapter(0, "Intro"),
+                    chapter(90, "Cornhole trick shots"),
+                    chapter(725, "Brick by brick"),
+                    chapter(3490, "Bottle flip finale"),
+                    chapter(3764, "Bloopers & outtakes"),
+                ]
+            )
+        );
+        // Two chapters, a first one past 0:00, out of order, closer than ten seconds
+        for (video_id, chapters) in &chapters[1..] {
+            assert_eq!(chapters, &[], "{}", video_id);
+        }
+        assert_eq!(chapters.len(), 5);
+    }
+
+    #[test]
+    fn chapters_start_at_zero() {
+        let text = "Recap of 1:00 to 2:00\n0:00 Intro\n0:30 One\n1:00 Two";
+        assert_eq!(
+            chapters(text),
+            [chapter(0, "Intro"), chapter(30, "One"), chapter(60, "Two")]
+        );
+        assert_eq!(chapters("0:15 Warmup\n3:00 One\n6:00 Two"), []);
+    }
+
+    #[test]
+    fn at_least_three_chapters() {
+        assert_eq!(chapters("0:00 Intro\n4:20 The shot"), []);
+    }
+
+    #[test]
+    fn chapters_ascend_ten_seconds_apart() {
+        assert_eq!(chapters("0:00 Intro\n5:00 One\n3:00 Two\n9:00 Three"), []);
+        assert_eq!(chapters("0:00 Intro\n0:05 Blink\n2:00 Main"), []);
+        assert_eq!(chapters("0:00 Intro\n0:10 Ten\n2:00 Main").len(), 3);
+    }
+
+    #[test]
+    fn clock_formats() {
+        assert_eq!(clock("3:15 Topic"), Some((4, 195)));
+        assert_eq!(clock("03:15"), Some((5, 195)));
+        assert_eq!(clock("1:03:15"), Some((7, 3795)));
+        assert_eq!(clock("1:3"), None);
+        assert_eq!(clock("3:75"), None);
+        assert_eq!(clock("123:00"), None);
+        assert_eq!(clock("2022"), None);
+    }
+
+    fn urls(extracted: &Extracted) -> Vec<&str> {
+        extracted
+            .links
+            .iter()
+            .map(|link| link.url.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn trailing_punctuation_is_not_part_of_a_url() {
+        let extracted = extract(Some(
+            "Merch at https://www.store.com/shop. Tickets (https://tix.example.com/a_(b)), \
+             see https://example.com/faq?q=1&amp;page=2!",
+        ));
+        assert_eq!(
+            urls(&extracted),
+            [
+                "https://www.store.com/shop",
+                "https://tix.example.com/a_(b)",
+                "https://example.com/faq?q=1&page=2",
+            ]
+        );
+        assert_eq!(extracted.links[0].domain, "store.com");
+    }
+
+    #[test]
+    fn a_fragment_is_not_a_hashtag() {
+        let extracted = extract(Some(
+            "Chapters at https://example.com/video#part2 #TrickShots #trickshots #2022",
+        ));
+        assert_eq!(urls(&extracted), ["https://example.com/video#part2"]);
+        assert_eq!(extracted.hashtags, ["trickshots"]);
+    }
+
+    #[test]
+    fn emails_are_not_mentions() {
+        let extracted = extract(Some(
+            "Business: team@dudeperfect.com. Follow @DudePerfect and @coby_cotton.",
+        ));
+        assert_eq!(extracted.mentions, ["dudeperfect", "coby_cotton"]);
+        assert!(extracted.links.is_empty());
+    }
+
+    #[test]
+    fn links_match_subdomains() {
+        let link = Link::new("https://shop.Example.com:8080/x").unwrap();
+        assert_eq!(link.domain, "shop.example.com");
+        assert!(link.is_to("example.com"));
+        assert!(link.is_to("www.shop.example.com"));
+        assert!(!link.is_to("ample.com"));
+        assert_eq!(Link::new("https://localhost/"), None);
+    }
+}
@@ -0,0 +1,266 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Records that end up in the cache, one per file
+ *
+*/
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::enrich::{Chapter, Link};
+use crate::kind::Kind;
+use crate::language::Language;
+use crate::stream::StreamState;
+use crate::timestamp::Timestamp;
+
+// Kind of document an entry was read from
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedType {
+    YouTube,
+    // Any other atom feed
+    Atom,
+    // RSS 2.0, podcasts among them
+    Rss,
+}
+
+impl FeedType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FeedType::YouTube => "youtube",
+            FeedType::Atom => "atom",
+            FeedType::Rss => "rss",
+        }
+    }
+}
+
+impl std::str::FromStr for FeedType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<FeedType, String> {
+        <FeedType as ValueEnum>::from_str(s, true)
+    }
+}
+
+impl std::fmt::Display for FeedType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// Entry as it is being parsed, also stored with nulls for incomplete entries
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct EntryOptional {
+    pub video_id: Option<String>,
+    pub channel_id: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub published: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<Kind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub views: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feed: Option<FeedType>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hashtags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mentions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sponsored: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sponsors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_normalized: Option<String>,
+    // Atom id or rss guid, the item id of feeds without yt:videoId
+    #[serde(skip)]
+    pub id: Option<String>,
+    // Alternate link and thumbnail width and height, only read to classify the entry
+    #[serde(skip)]
+    pub link: Option<String>,
+    #[serde(skip)]
+    pub thumbnail: Option<(u32, u32)>,
+}
+
+// Video id and channel id hold the item id and feed id of other feeds
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub video_id: String,
+    pub channel_id: String,
+    pub title: String,
+    pub author: String,
+    pub published: Timestamp,
+    // Left out of records when the feed has no updated date
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<Timestamp>,
+    // media:description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // Unset on records cached before entries were classified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<Kind>,
+    // media:statistics views at the time of the snapshot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub views: Option<u64>,
+    // Unset for regular uploads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamState>,
+    // Unset for youtube feeds, which is all the cache held before other feeds were read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feed: Option<FeedType>,
+    // Found in the description, see enrich
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hashtags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mentions: Vec<String>,
+    // In order of their start, empty unless the description has valid chapters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+    // A sponsor rule matched; sponsors are the ones the rules could name
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sponsored: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sponsors: Vec<String>,
+    // Detected from title and description, unset when neither has words to go by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    // Title as the title config cleans it, unset when it is not configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_normalized: Option<String>,
+}
+
+impl Entry {
+    pub fn is_youtube(&self) -> bool {
+        self.feed.is_none_or(|feed| feed == FeedType::YouTube)
+    }
+}
+
+impl EntryOptional {
+    pub fn is_youtube(&self) -> bool {
+        self.feed.is_none_or(|feed| feed == FeedType::YouTube)
+    }
+
+    // The entry when every field was found, itself otherwise
+    pub fn into_entry(self) -> Result<Entry, Box<EntryOptional>> {
+        match self {
+            EntryOptional {
+                video_id: Some(video_id),
+                channel_id: Some(channel_id),
+                title: Some(title),
+                author: Some(author),
+                published: Some(published),
+                updated,
+                description,
+                kind,
+                views,
+                stream,
+                feed,
+                links,
+                hashtags,
+                mentions,
+                chapters,
+                sponsored,
+                sponsors,
+                language,
+                title_normalized,
+                ..
+            } => Ok(Entry {
+                video_id,
+                channel_id,
+                title,
+                author,
+                published,
+                updated,
+                description,
+                kind,
+                views,
+                stream,
+                feed,
+                links,
+                hashtags,
+                mentions,
+                chapters,
+                sponsored,
+                sponsors,
+                language,
+                title_normalized,
+            }),
+            partial => Err(Box::new(partial)),
+        }
+    }
+}
+
+// Tombstone left behind by an at:deleted-entry push notification
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub video_id: String,
+    pub channel_id: Option<String>,
+    pub deleted: String,
+}
+
+// One per channel feed, so the cache knows the channel's own title
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Channel {
+    pub channel_id: String,
+    pub feed_title: String,
+}
+
+// An entry seen in a playlist feed, at its 0-based position in the playlist
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Membership {
+    pub playlist_id: String,
+    pub video_id: String,
+    pub position: u32,
+}
+
+// Anything parse can produce; stored untagged so entry files keep their shape.
+// Partial goes last, with every field optional it would match anything.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Record {
+    Entry(Entry),
+    Tombstone(Tombstone),
+    Channel(Channel),
+    Membership(Membership),
+    // Entry missing fields, kept when input.incomplete is nulls
+    Partial(EntryOptional),
+}
+
+pub const NULL_ENTRY: EntryOptional = EntryOptional {
+    video_id: None,
+    channel_id: None,
+    title: None,
+    author: None,
+    published: None,
+    updated: None,
+    description: None,
+    kind: None,
+    views: None,
+    stream: None,
+    feed: None,
+    links: Vec::new(),
+    hashtags: Vec::new(),
+    mentions: Vec::new(),
+    chapters: Vec::new(),
+    sponsored: false,
+    sponsors: Vec::new(),
+    language: None,
+    title_normalized: None,
+    id: None,
+    link: None,
+    thumbnail: None,
+};
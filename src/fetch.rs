@@ -0,0 +1,36 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Downloads channel feeds so parse has something to read
+ *
+*/
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::FetchConfig;
+use crate::opml::FEED_URL;
+use crate::Result;
+
+pub fn agent(config: &FetchConfig) -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(config.timeout_secs)))
+        .user_agent(config.user_agent.as_str())
+        .build()
+        .into()
+}
+
+// Function to download the raw feed xml of a single channel
+pub fn fetch_channel(agent: &ureq::Agent, channel_id: &str) -> Result<String> {
+    let url = format!("{}{}", FEED_URL, channel_id);
+    let body = agent.get(&url).call()?.body_mut().read_to_string()?;
+    Ok(body)
+}
+
+// Save a fetched feed as <channel id>-<unix seconds>.xml so earlier snapshots are kept
+pub fn write_feed(dst: &Path, channel_id: &str, xml: &str) -> Result<PathBuf> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let file_path = dst.join(format!("{}-{}.xml", channel_id, now));
+
+    std::fs::write(&file_path, xml)?;
+    Ok(file_path)
+}
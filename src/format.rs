@@ -0,0 +1,451 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Encoding of cache records, json or capnp
+ *
+*/
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::enrich::{Chapter, Link};
+use crate::entry::{Channel, EntryOptional, FeedType, Membership, Record, Tombstone, NULL_ENTRY};
+use crate::kind::Kind;
+use crate::language::Language;
+use crate::rss_capnp::r_s_s;
+use crate::stream::StreamState;
+use crate::timestamp::Timestamp;
+use crate::Result;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Json,
+    Capnp,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Capnp => "capnp",
+        }
+    }
+
+    // Format a cache file was written in, going by its extension
+    pub fn from_extension(ext: &str) -> Option<Format> {
+        match ext {
+            "json" => Some(Format::Json),
+            "capnp" => Some(Format::Capnp),
+            _ => None,
+        }
+    }
+
+    pub fn encode(self, record: &Record) -> Result<Vec<u8>> {
+        match self {
+            Format::Json => Ok(serde_json::to_vec(record)?),
+            Format::Capnp => encode_capnp(record),
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> Result<Record> {
+        match self {
+            Format::Json => Ok(serde_json::from_slice(bytes)?),
+            Format::Capnp => decode_capnp(bytes),
+        }
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Format, String> {
+        Format::from_extension(s).ok_or_else(|| format!("unknown format {}", s))
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+fn encode_capnp(record: &Record) -> Result<Vec<u8>> {
+    let mut message = capnp::message::Builder::new_default();
+    let mut rss = message.init_root::<r_s_s::Builder>();
+    match record {
+        Record::Entry(entry) => {
+            rss.set_video_id(&entry.video_id);
+            rss.set_channel_id(&entry.channel_id);
+            rss.set_title(&entry.title);
+            if let Some(title_normalized) = &entry.title_normalized {
+                rss.set_title_normalized(title_normalized);
+            }
+            rss.set_author(&entry.author);
+            set_published(&mut rss, Some(&entry.published));
+            set_updated(&mut rss, entry.updated.as_ref());
+            set_extras(&mut rss, entry.description.as_deref(), entry.kind);
+            set_stream(&mut rss, entry.views, entry.stream);
+            if let Some(feed) = entry.feed {
+                rss.set_feed(feed.as_str());
+            }
+            set_extracted(&mut rss, &entry.links, &entry.hashtags, &entry.mentions);
+            set_chapters(&mut rss, &entry.chapters);
+            set_sponsors(&mut rss, entry.sponsored, &entry.sponsors);
+            set_language(&mut rss, entry.language.as_ref());
+        }
+        Record::Tombstone(tombstone) => {
+            rss.set_video_id(&tombstone.video_id);
+            if let Some(channel_id) = &tombstone.channel_id {
+                rss.set_channel_id(channel_id);
+            }
+            rss.set_deleted(&tombstone.deleted);
+        }
+        Record::Channel(channel) => {
+            rss.set_channel_id(&channel.channel_id);
+            rss.set_feed_title(&channel.feed_title);
+        }
+        Record::Membership(membership) => {
+            rss.set_video_id(&membership.video_id);
+            rss.set_playlist_id(&membership.playlist_id);
+            rss.set_position(membership.position);
+        }
+        // Missing fields are left unset so decoding can tell them from empty ones
+        Record::Partial(partial) => {
+            if let Some(video_id) = &partial.video_id {
+                rss.set_video_id(video_id);
+            }
+            if let Some(channel_id) = &partial.channel_id {
+                rss.set_channel_id(channel_id);
+            }
+            if let Some(title) = &partial.title {
+                rss.set_title(title);
+            }
+            if let Some(title_normalized) = &partial.title_normalized {
+                rss.set_title_normalized(title_normalized);
+            }
+            if let Some(author) = &partial.author {
+                rss.set_author(author);
+            }
+            set_published(&mut rss, partial.published.as_ref());
+            set_updated(&mut rss, partial.updated.as_ref());
+            set_extras(&mut rss, partial.description.as_deref(), partial.kind);
+            set_stream(&mut rss, partial.views, partial.stream);
+            if let Some(feed) = partial.feed {
+                rss.set_feed(feed.as_str());
+            }
+            set_extracted(
+                &mut rss,
+                &partial.links,
+                &partial.hashtags,
+                &partial.mentions,
+            );
+            set_chapters(&mut rss, &partial.chapters);
+            set_sponsors(&mut rss, partial.sponsored, &partial.sponsors);
+            set_language(&mut rss, partial.language.as_ref());
+        }
+    }
+
+    let mut bytes = Vec::new();
+    capnp::serialize::write_message(&mut bytes, &message)?;
+    Ok(bytes)
+}
+
+fn decode_capnp(mut bytes: &[u8]) -> Result<Record> {
+    let message = capnp::serialize::read_message(&mut bytes, capnp::message::ReaderOptions::new())?;
+    let rss = message.get_root::<r_s_s::Reader>()?;
+    if rss.has_playlist_id() {
+        return Ok(Record::Membership(Membership {
+            playlist_id: rss.get_playlist_id()?.to_string(),
+            video_id: rss.get_video_id()?.to_string(),
+            position: rss.get_position(),
+        }));
+    }
+    if rss.has_deleted() {
+        return Ok(Record::Tombstone(Tombstone {
+            video_id: rss.get_video_id()?.to_string(),
+            channel_id: match rss.has_channel_id() {
+                true => Some(rss.get_channel_id()?.to_string()),
+                false => None,
+            },
+            deleted: rss.get_deleted()?.to_string(),
+        }));
+    }
+
+    if rss.has_feed_title() {
+        return Ok(Record::Channel(Channel {
+            channel_id: rss.get_channel_id()?.to_string(),
+            feed_title: rss.get_feed_title()?.to_string(),
+        }));
+    }
+
+    let partial = EntryOptional {
+        video_id: optional_text(rss.has_video_id(), || rss.get_video_id())?,
+        channel_id: optional_text(rss.has_channel_id(), || rss.get_channel_id())?,
+        title: optional_text(rss.has_title(), || rss.get_title())?,
+        author: optional_text(rss.has_author(), || rss.get_author())?,
+        published: get_published(rss)?,
+        updated: match rss.get_has_updated() {
+            true => Some(Timestamp::from_millis(
+                rss.get_updated_millis(),
+                rss.get_updated_offset(),
+            )?),
+            false => None,
+        },
+        description: optional_text(rss.has_description(), || rss.get_description())?,
+        kind: match rss.has_kind() {
+            true => Some(rss.get_kind()?.parse::<Kind>()?),
+            false => None,
+        },
+        views: rss.get_has_views().then(|| rss.get_views()),
+        stream: match rss.has_stream() {
+            true => Some(rss.get_stream()?.parse::<StreamState>()?),
+            false => None,
+        },
+        feed: match rss.has_feed() {
+            true => Some(rss.get_feed()?.parse::<FeedType>()?),
+            false => None,
+        },
+        links: text_list(rss.has_links(), || rss.get_links())?
+            .iter()
+            .filter_map(|url| Link::new(url))
+            .collect(),
+        hashtags: text_list(rss.has_hashtags(), || rss.get_hashtags())?,
+        mentions: text_list(rss.has_mentions(), || rss.get_mentions())?,
+        chapters: get_chapters(rss)?,
+        sponsored: rss.get_sponsored(),
+        sponsors: text_list(rss.has_sponsors(), || rss.get_sponsors())?,
+        language: match rss.has_language() {
+            true => Some(Language {
+                code: rss.get_language()?.to_string(),
+                confidence: rss.get_language_confidence(),
+            }),
+            false => None,
+        },
+        title_normalized: optional_text(rss.has_title_normalized(), || rss.get_title_normalized())?,
+        ..NULL_ENTRY
+    };
+    Ok(match partial.into_entry() {
+        Ok(entry) => Record::Entry(entry),
+        Err(partial) => Record::Partial(*partial),
+    })
+}
+
+fn set_published(rss: &mut r_s_s::Builder, published: Option<&Timestamp>) {
+    if let Some(published) = published {
+        rss.set_published_millis(published.millis());
+        rss.set_published_offset(published.offset_secs());
+        rss.set_has_published(true);
+    }
+}
+
+fn set_updated(rss: &mut r_s_s::Builder, updated: Option<&Timestamp>) {
+    if let Some(updated) = updated {
+        rss.set_updated_millis(updated.millis());
+        rss.set_updated_offset(updated.offset_secs());
+        rss.set_has_updated(true);
+    }
+}
+
+fn set_extras(rss: &mut r_s_s::Builder, description: Option<&str>, kind: Option<Kind>) {
+    if let Some(description) = description {
+        rss.set_description(description);
+    }
+    if let Some(kind) = kind {
+        rss.set_kind(kind.as_str());
+    }
+}
+
+fn set_stream(rss: &mut r_s_s::Builder, views: Option<u64>, stream: Option<StreamState>) {
+    if let Some(views) = views {
+        rss.set_views(views);
+        rss.set_has_views(true);
+    }
+    if let Some(stream) = stream {
+        rss.set_stream(stream.as_str());
+    }
+}
+
+// Lists are left unset when empty, like the optional text fields
+fn set_extracted(
+    rss: &mut r_s_s::Builder,
+    links: &[Link],
+    hashtags: &[String],
+    mentions: &[String],
+) {
+    if !links.is_empty() {
+        let list = rss.reborrow().init_links(links.len() as u32);
+        fill_text_list(list, links.iter().map(|link| link.url.as_str()));
+    }
+    if !hashtags.is_empty() {
+        let list = rss.reborrow().init_hashtags(hashtags.len() as u32);
+        fill_text_list(list, hashtags.iter().map(String::as_str));
+    }
+    if !mentions.is_empty() {
+        let list = rss.reborrow().init_mentions(mentions.len() as u32);
+        fill_text_list(list, mentions.iter().map(String::as_str));
+    }
+}
+
+fn set_chapters(rss: &mut r_s_s::Builder, chapters: &[Chapter]) {
+    if chapters.is_empty() {
+        return;
+    }
+    let mut starts = rss.reborrow().init_chapter_starts(chapters.len() as u32);
+    for (i, chapter) in chapters.iter().enumerate() {
+        starts.set(i as u32, chapter.start_secs);
+    }
+    let titles = rss.reborrow().init_chapter_titles(chapters.len() as u32);
+    fill_text_list(
+        titles,
+        chapters.iter().map(|chapter| chapter.title.as_str()),
+    );
+}
+
+fn set_sponsors(rss: &mut r_s_s::Builder, sponsored: bool, sponsors: &[String]) {
+    rss.set_sponsored(sponsored);
+    if !sponsors.is_empty() {
+        let list = rss.reborrow().init_sponsors(sponsors.len() as u32);
+        fill_text_list(list, sponsors.iter().map(String::as_str));
+    }
+}
+
+fn set_language(rss: &mut r_s_s::Builder, language: Option<&Language>) {
+    if let Some(language) = language {
+        rss.set_language(&language.code);
+        rss.set_language_confidence(language.confidence);
+    }
+}
+
+fn get_chapters(rss: r_s_s::Reader) -> Result<Vec<Chapter>> {
+    if !rss.has_chapter_starts() {
+        return Ok(Vec::new());
+    }
+    let starts = rss.get_chapter_starts()?;
+    let titles = text_list(rss.has_chapter_titles(), || rss.get_chapter_titles())?;
+    if starts.len() as usize != titles.len() {
+        return Err("chapter starts and titles differ in length".into());
+    }
+
+    Ok(starts
+        .iter()
+        .zip(titles)
+        .map(|(start_secs, title)| Chapter { start_secs, title })
+        .collect())
+}
+
+fn fill_text_list<'a>(mut list: capnp::text_list::Builder, items: impl Iterator<Item = &'a str>) {
+    for (i, item) in items.enumerate() {
+        list.set(i as u32, item);
+    }
+}
+
+// Files written before timestamps were typed carry published as text
+fn get_published(rss: r_s_s::Reader) -> Result<Option<Timestamp>> {
+    if rss.has_published() {
+        return Ok(Some(rss.get_published()?.parse()?));
+    }
+    match rss.get_has_published() {
+        true => Ok(Some(Timestamp::from_millis(
+            rss.get_published_millis(),
+            rss.get_published_offset(),
+        )?)),
+        false => Ok(None),
+    }
+}
+
+fn text_list<'a>(
+    has: bool,
+    get: impl FnOnce() -> capnp::Result<capnp::text_list::Reader<'a>>,
+) -> Result<Vec<String>> {
+    if !has {
+        return Ok(Vec::new());
+    }
+    let mut items = Vec::new();
+    for item in get()?.iter() {
+        items.push(item?.to_string());
+    }
+    Ok(items)
+}
+
+fn optional_text<'a>(
+    has: bool,
+    get: impl FnOnce() -> capnp::Result<capnp::text::Reader<'a>>,
+) -> Result<Option<String>> {
+    match has {
+        true => Ok(Some(get()?.to_string())),
+        false => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::Entry;
+
+    #[test]
+    fn records_round_trip() {
+        let records = [
+            Record::Entry(Entry {
+                video_id: "Ab3CdEfGhIj".to_string(),
+                channel_id: "UCRijo3ddMTht_IHyNSNXpNQ".to_string(),
+                title: "Q&amp;A".to_string(),
+                author: "Dude Perfect".to_string(),
+                published: "2022-11-05T13:59:57+00:00".parse().unwrap(),
+                updated: Some("2022-11-05T22:46:03+02:00".parse().unwrap()),
+                description: Some("Line one\nline two".to_string()),
+                kind: Some(Kind::Short),
+                views: Some(1234),
+                stream: Some(StreamState::Ended),
+                feed: None,
+                links: vec![Link::new("https://store.dudeperfect.com/").unwrap()],
+                hashtags: vec!["trickshots".to_string()],
+                mentions: vec!["dudeperfect".to_string()],
+                chapters: vec![
+                    Chapter {
+                        start_secs: 0,
+                        title: "Intro".to_string(),
+                    },
+                    Chapter {
+                        start_secs: 75,
+                        title: "Bottle flips".to_string(),
+                    },
+                ],
+                sponsored: true,
+                sponsors: vec!["store.dudeperfect.com".to_string()],
+                language: Some(Language {
+                    code: "eng".to_string(),
+                    confidence: 97,
+                }),
+                title_normalized: Some("Q&A".to_string()),
+            }),
+            Record::Tombstone(Tombstone {
+                video_id: "Ab3CdEfGhIj".to_string(),
+                channel_id: None,
+                deleted: "2022-11-06T10:00:00+00:00".to_string(),
+            }),
+            Record::Channel(Channel {
+                channel_id: "UCRijo3ddMTht_IHyNSNXpNQ".to_string(),
+                feed_title: "Dude Perfect".to_string(),
+            }),
+            Record::Membership(Membership {
+                playlist_id: "PLcirGkCPmbmFeQ1sm4wFciF03D_EroIfr".to_string(),
+                video_id: "Ab3CdEfGhIj".to_string(),
+                position: 3,
+            }),
+            Record::Partial(EntryOptional {
+                video_id: Some("Ab3CdEfGhIj".to_string()),
+                title: Some(String::new()),
+                kind: Some(Kind::Video),
+                stream: Some(StreamState::Upcoming),
+                feed: Some(FeedType::Rss),
+                ..NULL_ENTRY
+            }),
+        ];
+        for format in [Format::Json, Format::Capnp] {
+            for record in &records {
+                let bytes = format.encode(record).unwrap();
+                assert_eq!(&format.decode(&bytes).unwrap(), record, "{}", format);
+            }
+        }
+    }
+}
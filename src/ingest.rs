@@ -0,0 +1,354 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Parses one input file into a sink, consulting the ingest manifest
+ *
+*/
+
+use std::fs::File;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info_span, warn};
+
+use crate::budget::ByteBudget;
+use crate::cache::{self, Written};
+use crate::config::{Config, InputConfig};
+use crate::enrich;
+use crate::entry::Record;
+use crate::kind::KindOverrides;
+use crate::language::LanguageRules;
+use crate::manifest::{Ingested, Manifest, Stat};
+use crate::parse::{parse_from, Parsed};
+use crate::sink::Sink;
+use crate::source;
+use crate::sponsor::SponsorRules;
+use crate::stream::StreamRules;
+use crate::title::TitleCleaner;
+use crate::Result;
+
+// What happened to a single feed file during parse
+pub enum Outcome {
+    // Size and mtime match the manifest, the file was not read
+    Unchanged,
+    // Only the mtime moved, the content hash still matches the manifest
+    Touched(PathBuf, Ingested),
+    Ingested(PathBuf, Ingested, FileStats),
+}
+
+// Records a single file produced
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct FileStats {
+    pub entries: usize,
+    pub tombstones: usize,
+    pub memberships: usize,
+    // Records already in the cache
+    pub duplicates: usize,
+    // Entries missing fields, by what was done with them
+    pub skipped: usize,
+    pub nulls: usize,
+    pub quarantined: usize,
+}
+
+// What to do with an entry missing any of its five fields
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum IncompletePolicy {
+    // Leave it out with a warning
+    #[default]
+    Skip,
+    // Write it with the missing fields as nulls
+    Nulls,
+    // Leave it out and save its raw xml for inspection
+    Quarantine,
+}
+
+impl std::str::FromStr for IncompletePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<IncompletePolicy, String> {
+        <IncompletePolicy as ValueEnum>::from_str(s, true)
+    }
+}
+
+pub struct Incomplete {
+    pub policy: IncompletePolicy,
+    // Where quarantined entries go, required by the quarantine policy
+    pub quarantine: Option<PathBuf>,
+}
+
+impl Incomplete {
+    pub fn new(config: &InputConfig) -> Incomplete {
+        Incomplete {
+            policy: config.incomplete,
+            quarantine: config.quarantine.clone(),
+        }
+    }
+
+    // Saved as <md5 of the xml>.xml, so the same entry seen again is one file
+    fn quarantine(&self, raw: &str) -> Result<PathBuf> {
+        let dir = self
+            .quarantine
+            .as_ref()
+            .ok_or("input.incomplete is quarantine but input.quarantine is not set")?;
+        let path = dir.join(format!("{}.xml", cache::get_md5_hash(raw)));
+        if !path.exists() {
+            std::fs::write(&path, raw)?;
+        }
+
+        Ok(path)
+    }
+}
+
+// What happens to parsed records on their way to the sink
+pub struct Pipeline {
+    pub incomplete: Incomplete,
+    pub kinds: KindOverrides,
+    pub streams: StreamRules,
+    pub sponsors: SponsorRules,
+    pub languages: LanguageRules,
+    pub titles: TitleCleaner,
+}
+
+impl Pipeline {
+    pub fn new(config: &Config) -> Result<Pipeline> {
+        Ok(Pipeline {
+            incomplete: Incomplete::new(&config.input),
+            kinds: KindOverrides::new(&config.kind),
+            streams: StreamRules::new(&config.stream),
+            sponsors: SponsorRules::new(&config.sponsor)?,
+            languages: LanguageRules::new(&config.language),
+            titles: TitleCleaner::new(&config.title),
+        })
+    }
+
+    // Every entry gets its title cleaned, is enriched from its description and
+    // checked for sponsors and language; kinds and stream states only mean
+    // something for youtube entries
+    fn apply(&self, record: &mut Record) {
+        match record {
+            Record::Entry(entry) => {
+                entry.title_normalized = self.titles.clean(&enrich::unescape(&entry.title));
+                let extracted = enrich::extract(entry.description.as_deref());
+                entry.links = extracted.links;
+                entry.hashtags = extracted.hashtags;
+                entry.mentions = extracted.mentions;
+                entry.chapters = extracted.chapters;
+                let detected = self.sponsors.detect(
+                    Some(&entry.title),
+                    entry.description.as_deref(),
+                    &entry.links,
+                );
+                entry.sponsored = detected.sponsored;
+                entry.sponsors = detected.sponsors;
+                entry.language = self
+                    .languages
+                    .detect(Some(&entry.title), entry.description.as_deref());
+                if !entry.is_youtube() {
+                    return;
+                }
+                if let Some(kind) = self.kinds.get(&entry.channel_id) {
+                    entry.kind = Some(kind);
+                }
+                entry.stream = self.streams.detect(
+                    Some(&entry.title),
+                    entry.views,
+                    Some(entry.published),
+                    entry.updated,
+                );
+            }
+            Record::Partial(partial) => {
+                partial.title_normalized = partial
+                    .title
+                    .as_deref()
+                    .and_then(|title| self.titles.clean(&enrich::unescape(title)));
+                let extracted = enrich::extract(partial.description.as_deref());
+                partial.links = extracted.links;
+                partial.hashtags = extracted.hashtags;
+                partial.mentions = extracted.mentions;
+                partial.chapters = extracted.chapters;
+                let detected = self.sponsors.detect(
+                    partial.title.as_deref(),
+                    partial.description.as_deref(),
+                    &partial.links,
+                );
+                partial.sponsored = detected.sponsored;
+                partial.sponsors = detected.sponsors;
+                partial.language = self
+                    .languages
+                    .detect(partial.title.as_deref(), partial.description.as_deref());
+                if !partial.is_youtube() {
+                    return;
+                }
+                if let Some(kind) = partial
+                    .channel_id
+                    .as_deref()
+                    .and_then(|id| self.kinds.get(id))
+                {
+                    partial.kind = Some(kind);
+                }
+                partial.stream = self.streams.detect(
+                    partial.title.as_deref(),
+                    partial.views,
+                    partial.published,
+                    partial.updated,
+                );
+            }
+            Record::Tombstone(_) | Record::Channel(_) | Record::Membership(_) => {}
+        }
+    }
+}
+
+// Write every record of a feed document, counting what happened to them
+pub fn write_feed(
+    input: impl BufRead,
+    sink: &Sink,
+    pipeline: &Pipeline,
+    stats: &mut FileStats,
+) -> Result<()> {
+    let incomplete = &pipeline.incomplete;
+    parse_from(input, |parsed| {
+        let mut record = match parsed {
+            Parsed::Record(record) => record,
+            Parsed::Incomplete(partial, raw) => match incomplete.policy {
+                IncompletePolicy::Skip => {
+                    warn!(video_id = ?partial.video_id, "skipping incomplete entry");
+                    stats.skipped += 1;
+                    return Ok(());
+                }
+                IncompletePolicy::Nulls => Record::Partial(partial),
+                IncompletePolicy::Quarantine => {
+                    let path = incomplete.quarantine(&raw)?;
+                    warn!(video_id = ?partial.video_id, file = %path.display(), "quarantined incomplete entry");
+                    stats.quarantined += 1;
+                    return Ok(());
+                }
+            },
+        };
+        pipeline.apply(&mut record);
+
+        match sink.write(&record)? {
+            Written::Duplicate => stats.duplicates += 1,
+            Written::New => match record {
+                Record::Entry(_) => stats.entries += 1,
+                Record::Tombstone(_) => stats.tombstones += 1,
+                Record::Membership(_) => stats.memberships += 1,
+                Record::Partial(_) => stats.nulls += 1,
+                // Channel records ride along with every feed and are not counted
+                Record::Channel(_) => (),
+            },
+        }
+        Ok(())
+    })
+}
+
+// The file is only read once its size fits in the in-flight budget
+pub fn ingest_file(
+    file: &Path,
+    manifest: &Manifest,
+    full: bool,
+    sink: &Sink,
+    budget: &ByteBudget,
+    pipeline: &Pipeline,
+) -> Result<Outcome> {
+    let _span = info_span!("file", path = %file.display()).entered();
+    let key = std::fs::canonicalize(file)?;
+    let stat = Stat::of(file)?;
+    if !full && manifest.is_unchanged(&key, stat) {
+        return Ok(Outcome::Unchanged);
+    }
+
+    let _permit = budget.acquire(stat.size);
+    let ingested = Ingested {
+        size: stat.size,
+        mtime: stat.mtime,
+        md5: cache::get_file_md5(file)?,
+    };
+    if !full && manifest.has_content(&key, &ingested.md5) {
+        return Ok(Outcome::Touched(key, ingested));
+    }
+
+    let mut stats = FileStats::default();
+    let input = File::open(file)?;
+    source::for_each_feed(file, input, |_, feed| {
+        write_feed(feed, sink, pipeline, &mut stats)
+    })?;
+    debug!(
+        entries = stats.entries,
+        tombstones = stats.tombstones,
+        memberships = stats.memberships,
+        duplicates = stats.duplicates,
+        incomplete = stats.skipped + stats.nulls + stats.quarantined,
+        "parsed"
+    );
+    Ok(Outcome::Ingested(key, ingested, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kind::Kind;
+    use crate::parse;
+
+    fn records(xml: &str, config: &Config) -> Vec<Record> {
+        let pipeline = Pipeline::new(config).unwrap();
+        let mut records = parse::records(xml);
+        for record in &mut records {
+            pipeline.apply(record);
+        }
+        records
+    }
+
+    fn kinds(records: &[Record]) -> Vec<Option<Kind>> {
+        records
+            .iter()
+            .filter_map(|record| match record {
+                Record::Entry(entry) => Some(entry.kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn kind_overrides_win_over_the_heuristics() {
+        let xml = include_str!("../data/src/shorts.xml");
+        let short = Some(Kind::Short);
+        let video = Some(Kind::Video);
+        assert_eq!(
+            kinds(&records(xml, &Config::default())),
+            [short, short, short, video]
+        );
+
+        let mut config = Config::default();
+        config.kind.videos = vec!["UCRijo3ddMTht_IHyNSNXpNQ".to_string()];
+        assert_eq!(kinds(&records(xml, &config)), [video; 4]);
+        config.kind.videos.clear();
+        config.kind.shorts = vec!["UCRijo3ddMTht_IHyNSNXpNQ".to_string()];
+        assert_eq!(kinds(&records(xml, &config)), [short; 4]);
+    }
+
+    #[test]
+    fn titles_are_unescaped_before_cleaning() {
+        let xml = include_str!("../data/src/titles.xml").replace(" vs ", " &amp; ");
+        let mut config = Config::default();
+        config.title.normalize = Some(crate::title::NormalForm::Nfc);
+        let titles: Vec<_> = records(&xml, &config)
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::Entry(entry) => Some((entry.title, entry.title_normalized)),
+                _ => None,
+            })
+            .collect();
+        assert!(titles.iter().any(|(title, _)| title.contains("&amp;")));
+        assert!(
+            titles
+                .iter()
+                .any(|(_, normalized)| normalized.as_deref()
+                    == Some("Café & Café – Which Is Better?"))
+        );
+        for (title, normalized) in &titles {
+            assert!(!normalized.as_deref().unwrap().contains("&amp;"), "{title}");
+        }
+    }
+}
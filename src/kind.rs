@@ -0,0 +1,195 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Tells Shorts apart from regular uploads
+ *
+*/
+
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::config::KindConfig;
+use crate::entry::EntryOptional;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Video,
+    Short,
+}
+
+impl Kind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Kind::Video => "video",
+            Kind::Short => "short",
+        }
+    }
+}
+
+impl std::str::FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Kind, String> {
+        <Kind as ValueEnum>::from_str(s, true)
+    }
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// A #shorts or #short hashtag, not #shortsfilm or #shortstory
+fn has_shorts_tag(text: &str) -> bool {
+    text.match_indices('#').any(|(at, _)| {
+        let tag: String = text[at + 1..]
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        tag.eq_ignore_ascii_case("shorts") || tag.eq_ignore_ascii_case("short")
+    })
+}
+
+// Classify from what the feed says about an entry, strongest hint first: a
+// /shorts/ link, a portrait thumbnail, then a shorts hashtag in title or description
+pub fn classify(entry: &EntryOptional) -> Kind {
+    let link = entry.link.as_deref().unwrap_or_default();
+    let portrait = entry
+        .thumbnail
+        .is_some_and(|(width, height)| height > width);
+    let tagged = [&entry.title, &entry.description]
+        .iter()
+        .filter_map(|text| text.as_deref())
+        .any(has_shorts_tag);
+
+    if link.contains("/shorts/") || portrait || tagged {
+        Kind::Short
+    } else {
+        Kind::Video
+    }
+}
+
+// Channels known to upload only one kind, overriding the heuristics
+pub struct KindOverrides {
+    shorts: HashSet<String>,
+    videos: HashSet<String>,
+}
+
+impl KindOverrides {
+    pub fn new(config: &KindConfig) -> KindOverrides {
+        KindOverrides {
+            shorts: config.shorts.iter().cloned().collect(),
+            videos: config.videos.iter().cloned().collect(),
+        }
+    }
+
+    // Kind every upload of the channel has, when it is listed
+    pub fn get(&self, channel_id: &str) -> Option<Kind> {
+        if self.shorts.contains(channel_id) {
+            Some(Kind::Short)
+        } else if self.videos.contains(channel_id) {
+            Some(Kind::Video)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::NULL_ENTRY;
+    use crate::parse::entries;
+
+    fn entry(link: &str, thumbnail: (u32, u32), title: &str) -> EntryOptional {
+        EntryOptional {
+            title: Some(title.to_string()),
+            link: Some(link.to_string()),
+            thumbnail: Some(thumbnail),
+            ..NULL_ENTRY
+        }
+    }
+
+    const WATCH: &str = "https://www.youtube.com/watch?v=L0ngF0rm004";
+    const LANDSCAPE: (u32, u32) = (480, 360);
+
+    #[test]
+    fn shorts_link() {
+        let short = entry(
+            "https://www.youtube.com/shorts/Sh0rtL1nk01",
+            LANDSCAPE,
+            "Trick shot",
+        );
+        assert_eq!(classify(&short), Kind::Short);
+    }
+
+    #[test]
+    fn portrait_thumbnail() {
+        assert_eq!(
+            classify(&entry(WATCH, (1080, 1920), "Bottle flip")),
+            Kind::Short
+        );
+        assert_eq!(
+            classify(&entry(WATCH, (1920, 1080), "Bottle flip")),
+            Kind::Video
+        );
+    }
+
+    #[test]
+    fn shorts_hashtags() {
+        assert_eq!(
+            classify(&entry(WATCH, LANDSCAPE, "Ping pong #Shorts")),
+            Kind::Short
+        );
+        assert_eq!(
+            classify(&entry(WATCH, LANDSCAPE, "Ping pong #short")),
+            Kind::Short
+        );
+        assert_eq!(
+            classify(&entry(WATCH, LANDSCAPE, "Ping pong #shortsfilm")),
+            Kind::Video
+        );
+
+        let tagged = EntryOptional {
+            description: Some("Out now #shorts".to_string()),
+            ..entry(WATCH, LANDSCAPE, "Ping pong")
+        };
+        assert_eq!(classify(&tagged), Kind::Short);
+        let story = EntryOptional {
+            description: Some("Full episode, not a #shortstory".to_string()),
+            ..entry(WATCH, LANDSCAPE, "Overtime 31")
+        };
+        assert_eq!(classify(&story), Kind::Video);
+    }
+
+    #[test]
+    fn fixture_entries_are_classified() {
+        let kinds: Vec<(String, Option<Kind>)> = entries(include_str!("../data/src/shorts.xml"))
+            .into_iter()
+            .map(|entry| (entry.video_id, entry.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("Sh0rtL1nk01".to_string(), Some(Kind::Short)),
+                ("Sh0rtThumb2".to_string(), Some(Kind::Short)),
+                ("Sh0rtTag003".to_string(), Some(Kind::Short)),
+                ("L0ngF0rm004".to_string(), Some(Kind::Video)),
+            ]
+        );
+    }
+
+    #[test]
+    fn overrides_by_channel() {
+        let overrides = KindOverrides::new(&KindConfig {
+            shorts: vec!["UCshorts".to_string()],
+            videos: vec!["UCvideos".to_string()],
+        });
+        assert_eq!(overrides.get("UCshorts"), Some(Kind::Short));
+        assert_eq!(overrides.get("UCvideos"), Some(Kind::Video));
+        assert_eq!(overrides.get("UCother"), None);
+    }
+}
@@ -0,0 +1,178 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Guesses the language titles and descriptions are written in
+ *
+*/
+
+use serde::{Deserialize, Serialize};
+use whatlang::{Detector, Lang};
+
+use crate::config::LanguageConfig;
+use crate::enrich;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Language {
+    // ISO 639-3, e.g. "eng" or "deu"
+    pub code: String,
+    // How sure the detector is, from 0 to 100
+    pub confidence: u8,
+}
+
+impl Language {
+    pub fn is(&self, code: &str) -> bool {
+        self.code.eq_ignore_ascii_case(code.trim())
+    }
+}
+
+pub struct LanguageRules {
+    detector: Detector,
+    allowed: Vec<Lang>,
+    min_confidence: Option<u8>,
+}
+
+impl LanguageRules {
+    pub fn new(config: &LanguageConfig) -> LanguageRules {
+        // Unknown codes are reported by config validation
+        let allowed: Vec<Lang> = config.languages.iter().filter_map(|c| lang(c)).collect();
+        let detector = match allowed.is_empty() {
+            true => Detector::new(),
+            false => Detector::with_allowlist(allowed.clone()),
+        };
+
+        LanguageRules {
+            detector,
+            allowed,
+            min_confidence: config.min_confidence,
+        }
+    }
+
+    // None when there are no words to go by or the guess is too unsure to route
+    // content by; a couple of words in a title often give a wild one. Scripts
+    // written in a single language, like hangul or kana, bypass the detector's
+    // allowlist, so its answer is checked against the list again.
+    pub fn detect(&self, title: Option<&str>, description: Option<&str>) -> Option<Language> {
+        // Both are cached with their xml escapes
+        let text = [title, description].map(|text| text.map(enrich::unescape));
+        let text = text
+            .iter()
+            .flatten()
+            .flat_map(|text| text.split_whitespace())
+            .filter(|word| is_prose(word))
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let info = self.detector.detect(&text)?;
+        if !self.allowed.is_empty() && !self.allowed.contains(&info.lang()) {
+            return None;
+        }
+        let confidence = (info.confidence() * 100.0).round().clamp(0.0, 100.0) as u8;
+        let sure = match self.min_confidence {
+            Some(min) => confidence >= min,
+            None => info.is_reliable(),
+        };
+
+        sure.then(|| Language {
+            code: info.lang().code().to_string(),
+            confidence,
+        })
+    }
+}
+
+pub fn lang(code: &str) -> Option<Lang> {
+    Lang::from_code(code.trim().to_lowercase())
+}
+
+// Urls, hashtags, mentions and timestamps are left out, they read as english
+// whatever the language around them
+fn is_prose(word: &str) -> bool {
+    !word.contains("://") && !word.starts_with(['#', '@']) && word.chars().any(char::is_alphabetic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::entries;
+
+    fn detected(config: &LanguageConfig) -> Vec<(String, Option<String>)> {
+        let rules = LanguageRules::new(config);
+        entries(include_str!("../data/src/languages.xml"))
+            .into_iter()
+            .map(|entry| {
+                let language = rules.detect(Some(&entry.title), entry.description.as_deref());
+                (entry.video_id, language.map(|language| language.code))
+            })
+            .collect()
+    }
+
+    fn code(video_id: &str, code: Option<&str>) -> (String, Option<String>) {
+        (video_id.to_string(), code.map(String::from))
+    }
+
+    #[test]
+    fn fixture_languages() {
+        assert_eq!(
+            detected(&LanguageConfig::default()),
+            [
+                code("L4ngu4geDe1", Some("deu")),
+                code("L4ngu4geEs2", Some("spa")),
+                code("L4ngu4geJa3", Some("jpn")),
+                code("L4ngu4geEn4", Some("eng")),
+                code("L4ngu4geXx5", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn allowed_languages_only() {
+        let config = LanguageConfig {
+            languages: vec!["eng".to_string(), "DEU".to_string()],
+            ..LanguageConfig::default()
+        };
+        let detected = detected(&config);
+        assert_eq!(detected[0], code("L4ngu4geDe1", Some("deu")));
+        // Kana is japanese whatever the allowlist says
+        assert_eq!(detected[2], code("L4ngu4geJa3", None));
+    }
+
+    // Short titles alone give guesses too unsure to keep
+    #[test]
+    fn unsure_guesses_are_left_out() {
+        let rules = LanguageRules::new(&LanguageConfig::default());
+        assert_eq!(rules.detect(Some("Overtime 30"), None), None);
+        assert_eq!(rules.detect(Some("Complete live premiere"), None), None);
+
+        let anything = LanguageRules::new(&LanguageConfig {
+            min_confidence: Some(0),
+            ..LanguageConfig::default()
+        });
+        assert!(anything.detect(Some("Overtime 30"), None).is_some());
+        let strict = LanguageRules::new(&LanguageConfig {
+            min_confidence: Some(100),
+            ..LanguageConfig::default()
+        });
+        assert_eq!(strict.detect(Some("Overtime 30"), None), None);
+    }
+
+    // "&amp;" would otherwise count as a word
+    #[test]
+    fn escaped_titles_read_as_written() {
+        let rules = LanguageRules::new(&LanguageConfig {
+            min_confidence: Some(0),
+            ..LanguageConfig::default()
+        });
+        let title = "Tom & Jerry en español";
+        let escaped = title.replace('&', "&amp;");
+        let detected = rules.detect(Some(title), None);
+        assert_eq!(detected.as_ref().map(|l| l.code.as_str()), Some("spa"));
+        assert_eq!(rules.detect(Some(&escaped), None), detected);
+        assert_eq!(rules.detect(None, Some(&escaped)), detected);
+    }
+
+    #[test]
+    fn urls_and_tags_are_not_prose() {
+        assert!(is_prose("Trailer"));
+        assert!(!is_prose("https://www.netflix.com/title/80100172"));
+        assert!(!is_prose("#Netflix"));
+        assert!(!is_prose("@NetflixES"));
+        assert!(!is_prose("1:02:44"));
+    }
+}
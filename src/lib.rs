@@ -0,0 +1,36 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Shared code behind the youtube rss cached binaries
+ *
+*/
+
+pub mod budget;
+pub mod cache;
+pub mod cli;
+pub mod config;
+pub mod discover;
+pub mod enrich;
+pub mod entry;
+pub mod fetch;
+pub mod format;
+pub mod ingest;
+pub mod kind;
+pub mod language;
+pub mod log;
+pub mod manifest;
+pub mod opml;
+pub mod parse;
+pub mod progress;
+#[allow(clippy::all)]
+pub mod rss_capnp;
+pub mod sink;
+pub mod source;
+pub mod sponsor;
+pub mod stream;
+pub mod summary;
+pub mod timestamp;
+pub mod title;
+pub mod watch;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Result<T> = std::result::Result<T, Error>;
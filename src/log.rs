@@ -0,0 +1,41 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Sets up structured logging on stderr, text or json lines
+ *
+*/
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::EnvFilter;
+
+use crate::Result;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<LogFormat, String> {
+        <LogFormat as ValueEnum>::from_str(s, true)
+    }
+}
+
+// Level is an env filter directive like "info" or "youtube_rss_cached=debug"
+pub fn init(level: &str, format: LogFormat) -> Result<()> {
+    let filter = EnvFilter::try_new(level).map_err(|e| format!("log level {}: {}", level, e))?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => builder.try_init()?,
+        LogFormat::Json => builder.json().try_init()?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Remembers which feed files were already ingested so re-runs can skip them
+ *
+*/
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+// Default manifest name, kept next to the records in the cache directory
+pub const DEFAULT_NAME: &str = "ingest.manifest";
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Ingested {
+    pub size: u64,
+    // Modification time in nanoseconds since the epoch
+    pub mtime: u64,
+    pub md5: String,
+}
+
+// Size and mtime of a file, the cheap half of the change check
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Stat {
+    pub size: u64,
+    pub mtime: u64,
+}
+
+impl Stat {
+    pub fn of(path: &Path) -> Result<Stat> {
+        let meta = std::fs::metadata(path)?;
+        let mtime = meta.modified()?.duration_since(UNIX_EPOCH)?.as_nanos() as u64;
+        Ok(Stat {
+            size: meta.len(),
+            mtime,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Manifest {
+    path: PathBuf,
+    files: BTreeMap<PathBuf, Ingested>,
+}
+
+impl Manifest {
+    // Load the manifest at path, starting empty when it does not exist yet
+    pub fn load(path: &Path) -> Result<Manifest> {
+        let files = match std::fs::read(path) {
+            Ok(bytes) => {
+                serde_json::from_slice(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(format!("reading {}: {}", path.display(), e).into()),
+        };
+
+        Ok(Manifest {
+            path: path.to_path_buf(),
+            files,
+        })
+    }
+
+    // Write to a temporary file first so an interrupted save keeps the old manifest
+    pub fn save(&self) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&self.files)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    pub fn get(&self, file: &Path) -> Option<&Ingested> {
+        self.files.get(file)
+    }
+
+    // True when size and mtime match what was ingested, no need to read the file
+    pub fn is_unchanged(&self, file: &Path, stat: Stat) -> bool {
+        self.get(file)
+            .is_some_and(|ingested| ingested.size == stat.size && ingested.mtime == stat.mtime)
+    }
+
+    // True when the content hash matches what was ingested, e.g. after a touch
+    pub fn has_content(&self, file: &Path, md5: &str) -> bool {
+        self.get(file).is_some_and(|ingested| ingested.md5 == md5)
+    }
+
+    pub fn record(&mut self, file: PathBuf, ingested: Ingested) {
+        self.files.insert(file, ingested);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_and_touched_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let feed = dir.path().join("feed.xml");
+        std::fs::write(&feed, "<feed/>").unwrap();
+        let stat = Stat::of(&feed).unwrap();
+
+        let path = dir.path().join(DEFAULT_NAME);
+        let mut manifest = Manifest::load(&path).unwrap();
+        assert!(!manifest.is_unchanged(&feed, stat));
+        manifest.record(
+            feed.clone(),
+            Ingested {
+                size: stat.size,
+                mtime: stat.mtime,
+                md5: "0123456789abcdef0123456789abcdef".to_string(),
+            },
+        );
+        manifest.save().unwrap();
+
+        let manifest = Manifest::load(&path).unwrap();
+        assert!(manifest.is_unchanged(&feed, stat));
+
+        // A touch moves the mtime but keeps the content
+        let touched = Stat {
+            mtime: stat.mtime + 1,
+            ..stat
+        };
+        assert!(!manifest.is_unchanged(&feed, touched));
+        assert!(manifest.has_content(&feed, "0123456789abcdef0123456789abcdef"));
+        assert!(!manifest.has_content(&feed, "ffffffffffffffffffffffffffffffff"));
+
+        let grown = Stat {
+            size: stat.size + 1,
+            ..stat
+        };
+        assert!(!manifest.is_unchanged(&feed, grown));
+    }
+
+    #[test]
+    fn corrupt_manifest_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DEFAULT_NAME);
+        std::fs::write(&path, "not json").unwrap();
+        assert!(Manifest::load(&path).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Moves channel subscriptions in and out of OPML
+ *
+*/
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+
+use crate::entry::Record;
+use crate::Result;
+
+pub const FEED_URL: &str = "https://www.youtube.com/feeds/videos.xml?channel_id=";
+
+// Pull channel_id out of a feed url like .../feeds/videos.xml?channel_id=UC...
+pub fn channel_id_from_url(url: &str) -> Option<String> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("channel_id="))
+        .map(|id| id.to_string())
+}
+
+// Channel list is one channel id per line, blank lines and # comments ignored
+pub fn read_channel_list(path: &Path) -> Result<BTreeSet<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+        Err(e) => Err(format!("reading {}: {}", path.display(), e).into()),
+    }
+}
+
+pub fn write_channel_list(path: &Path, channels: &BTreeSet<String>) -> Result<()> {
+    let mut text = String::new();
+    for channel in channels {
+        text.push_str(channel);
+        text.push('\n');
+    }
+
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+// Collect channel ids from every outline xmlUrl in an OPML document
+pub fn parse_opml(xml: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut channels = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Err(e) => return Err(format!("at position {}: {}", reader.buffer_position(), e).into()),
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.name().as_ref() == b"outline" => {
+                for attr in e.attributes().flatten() {
+                    if attr.key.as_ref() == b"xmlUrl" {
+                        let url = attr.unescape_value()?;
+                        if let Some(channel_id) = channel_id_from_url(&url) {
+                            channels.push(channel_id);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    Ok(channels)
+}
+
+// Channels seen in the cache, keyed by yt:channelId with the feed title as value.
+// Titles are cached with their xml escapes and come out unescaped; a channel
+// only seen in entries and tombstones gets an empty title.
+pub fn cached_channels(records: &[Record]) -> BTreeMap<String, String> {
+    let mut channels = BTreeMap::new();
+    for record in records {
+        let (channel_id, title) = match record {
+            // Only youtube channels have a feed url to subscribe to
+            Record::Entry(entry) if !entry.is_youtube() => (None, None),
+            Record::Partial(partial) if !partial.is_youtube() => (None, None),
+            Record::Entry(entry) => (Some(&entry.channel_id), None),
+            Record::Tombstone(tombstone) => (tombstone.channel_id.as_ref(), None),
+            Record::Channel(channel) => (Some(&channel.channel_id), Some(&channel.feed_title)),
+            Record::Membership(_) => (None, None),
+            Record::Partial(partial) => (partial.channel_id.as_ref(), None),
+        };
+
+        if let Some(channel_id) = channel_id {
+            let known: &mut String = channels.entry(channel_id.clone()).or_default();
+            if known.is_empty() {
+                if let Some(title) = title {
+                    *known = unescape(title).unwrap_or(Cow::Borrowed(title)).into_owned();
+                }
+            }
+        }
+    }
+
+    channels
+}
+
+pub fn render_opml(channels: &BTreeMap<String, String>) -> String {
+    let mut opml = String::new();
+    opml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    opml.push_str("<opml version=\"1.1\">\n");
+    opml.push_str(" <head><title>YouTube Subscriptions</title></head>\n");
+    opml.push_str(" <body>\n");
+    opml.push_str("  <outline text=\"YouTube Subscriptions\" title=\"YouTube Subscriptions\">\n");
+    for (channel_id, title) in channels {
+        let title = if title.is_empty() { channel_id } else { title };
+        let title = escape(title);
+        let url = escape(&format!("{}{}", FEED_URL, channel_id)).into_owned();
+        opml.push_str(&format!(
+            "   <outline text=\"{}\" title=\"{}\" type=\"rss\" xmlUrl=\"{}\"/>\n",
+            title, title, url
+        ));
+    }
+    opml.push_str("  </outline>\n");
+    opml.push_str(" </body>\n");
+    opml.push_str("</opml>\n");
+
+    opml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::{Channel, Entry, Tombstone};
+
+    #[test]
+    fn channel_ids_from_feed_urls() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="1.1">
+ <body>
+  <outline text="YouTube Subscriptions" title="YouTube Subscriptions">
+   <outline text="Dude Perfect" type="rss" xmlUrl="https://www.youtube.com/feeds/videos.xml?channel_id=UCRijo3ddMTht_IHyNSNXpNQ"/>
+   <outline text="Blog" type="rss" xmlUrl="https://example.com/feed.xml"/>
+   <outline text="MrBeast" type="rss" xmlUrl="https://www.youtube.com/feeds/videos.xml?hl=en&amp;channel_id=UCX6OQ3DkcsbYNE6H8uQQuVA"/>
+  </outline>
+ </body>
+</opml>"#;
+        assert_eq!(
+            parse_opml(xml).unwrap(),
+            ["UCRijo3ddMTht_IHyNSNXpNQ", "UCX6OQ3DkcsbYNE6H8uQQuVA"]
+        );
+    }
+
+    #[test]
+    fn outlines_are_titled_by_the_feed() {
+        let records = [
+            Record::Entry(Entry {
+                video_id: "Ab3CdEfGhIj".to_string(),
+                channel_id: "UCa".to_string(),
+                title: "Q&amp;A".to_string(),
+                author: "Not the title".to_string(),
+                published: "2022-11-05T13:59:57+00:00".parse().unwrap(),
+                updated: None,
+                description: None,
+                kind: None,
+                views: None,
+                stream: None,
+                feed: None,
+                links: Vec::new(),
+                hashtags: Vec::new(),
+                mentions: Vec::new(),
+                chapters: Vec::new(),
+                sponsored: false,
+                sponsors: Vec::new(),
+                language: None,
+                title_normalized: None,
+            }),
+            Record::Channel(Channel {
+                channel_id: "UCa".to_string(),
+                feed_title: "A &amp; B".to_string(),
+            }),
+            Record::Tombstone(Tombstone {
+                video_id: "Kl4MnOpQrSt".to_string(),
+                channel_id: Some("UCb".to_string()),
+                deleted: "2022-11-06T10:00:00+00:00".to_string(),
+            }),
+        ];
+        let channels = cached_channels(&records);
+        assert_eq!(channels["UCa"], "A & B");
+        assert_eq!(channels["UCb"], "");
+
+        let opml = render_opml(&channels);
+        assert!(opml.contains(r#"<outline text="A &amp; B" title="A &amp; B" type="rss""#));
+        // Without a feed title the channel id stands in
+        assert!(opml.contains(r#"<outline text="UCb" title="UCb" type="rss""#));
+        assert_eq!(parse_opml(&opml).unwrap(), ["UCa", "UCb"]);
+    }
+}
@@ -0,0 +1,1078 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Turns a feed document, youtube, atom or rss 2.0, into cache records
+ *
+*/
+
+use std::io::{BufRead, Read};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{Namespace, QName, ResolveResult};
+use quick_xml::reader::NsReader;
+use tracing::warn;
+
+use crate::entry::{Channel, EntryOptional, FeedType, Membership, Record, Tombstone, NULL_ENTRY};
+use crate::kind;
+use crate::timestamp::Timestamp;
+use crate::Result;
+
+const ATOM: &[u8] = b"http://www.w3.org/2005/Atom";
+const YOUTUBE: &[u8] = b"http://www.youtube.com/xml/schemas/2015";
+const TOMBSTONES: &[u8] = b"http://purl.org/atompub/tombstones/1.0";
+const MEDIA: &[u8] = b"http://search.yahoo.com/mrss/";
+const DUBLIN_CORE: &[u8] = b"http://purl.org/dc/elements/1.1/";
+const ITUNES: &[u8] = b"http://www.itunes.com/dtds/podcast-1.0.dtd";
+
+// Namespaces elements are matched in, whatever prefix a feed binds them to
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Ns {
+    Atom,
+    YouTube,
+    Tombstones,
+    Media,
+    DublinCore,
+    Itunes,
+    Other,
+}
+
+impl Ns {
+    // Elements in no namespace count as atom, for feeds missing the default xmlns.
+    // Rss 2.0 elements have no namespace either and are matched as atom.
+    fn of(resolved: &ResolveResult) -> Ns {
+        match resolved {
+            ResolveResult::Bound(Namespace(ATOM)) | ResolveResult::Unbound => Ns::Atom,
+            ResolveResult::Bound(Namespace(YOUTUBE)) => Ns::YouTube,
+            ResolveResult::Bound(Namespace(TOMBSTONES)) => Ns::Tombstones,
+            ResolveResult::Bound(Namespace(MEDIA)) => Ns::Media,
+            ResolveResult::Bound(Namespace(DUBLIN_CORE)) => Ns::DublinCore,
+            ResolveResult::Bound(Namespace(ITUNES)) => Ns::Itunes,
+            _ => Ns::Other,
+        }
+    }
+}
+
+fn xml_error<R>(reader: &NsReader<R>, e: quick_xml::Error) -> crate::Error {
+    format!("Error at position {}: {:?}", reader.buffer_position(), e).into()
+}
+
+// Raw text up to the end of the element, entities left escaped as the cache has
+// always stored them. Text is not trimmed for the same reason, and comments or
+// markup inside the element are written back as they came.
+fn read_text<R: BufRead>(reader: &mut NsReader<R>, end: QName) -> Result<String> {
+    let end = end.as_ref().to_vec();
+    let mut text = Vec::new();
+    let mut buf = Vec::new();
+    let mut depth = 0;
+    reader.trim_text(false);
+    loop {
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(event) => event,
+            Err(e) => return Err(xml_error(reader, e)),
+        };
+        let (open, raw, close): (&[u8], &[u8], &[u8]) = match &event {
+            Event::Text(e) => (b"", e, b""),
+            Event::CData(e) => (b"<![CDATA[", e, b"]]>"),
+            Event::Comment(e) => (b"<!--", e, b"-->"),
+            Event::PI(e) => (b"<?", e, b"?>"),
+            Event::DocType(e) => (b"<!DOCTYPE ", e, b">"),
+            Event::Decl(e) => (b"<?", e, b"?>"),
+            Event::Empty(e) => (b"<", e, b"/>"),
+            Event::Start(e) => {
+                if e.name().as_ref() == end {
+                    depth += 1;
+                }
+                (b"<", e, b">")
+            }
+            Event::End(e) if e.name().as_ref() == end && depth == 0 => break,
+            Event::End(e) => {
+                if e.name().as_ref() == end {
+                    depth -= 1;
+                }
+                (b"</", e, b">")
+            }
+            Event::Eof => {
+                let end = String::from_utf8_lossy(&end);
+                return Err(format!("Error not find {} end element", end).into());
+            }
+        };
+        text.extend_from_slice(open);
+        text.extend_from_slice(raw);
+        text.extend_from_slice(close);
+        buf.clear();
+    }
+    reader.trim_text(true);
+
+    Ok(reader
+        .decoder()
+        .decode(&text)
+        .map_err(|e| xml_error(reader, e))?
+        .into_owned())
+}
+
+// A date element; one that is not rfc 3339 is left out with a warning, so its
+// entry goes through the incomplete policy rather than failing the document
+fn read_timestamp<R: BufRead>(reader: &mut NsReader<R>, end: QName) -> Result<Option<Timestamp>> {
+    let text = read_text(reader, end)?;
+    match text.parse() {
+        Ok(timestamp) => Ok(Some(timestamp)),
+        Err(e) => {
+            warn!(position = reader.buffer_position(), error = %e, "ignoring bad date");
+            Ok(None)
+        }
+    }
+}
+
+// Same for the rfc 2822 dates of rss
+fn read_pub_date<R: BufRead>(reader: &mut NsReader<R>, end: QName) -> Result<Option<Timestamp>> {
+    let text = read_text(reader, end)?;
+    match Timestamp::from_rfc2822(&text) {
+        Ok(timestamp) => Ok(Some(timestamp)),
+        Err(e) => {
+            warn!(position = reader.buffer_position(), error = %e, "ignoring bad date");
+            Ok(None)
+        }
+    }
+}
+
+// Function to handl parsing author from xml
+fn parse_author<R: BufRead>(reader: &mut NsReader<R>) -> Result<Option<String>> {
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_resolved_event_into(&mut buf) {
+            Ok((ns, Event::Start(e)))
+                if (Ns::of(&ns), e.local_name().as_ref()) == (Ns::Atom, b"name") =>
+            {
+                let name = read_text(reader, e.name())?;
+                return Ok(Some(name));
+            }
+            Ok((ns, Event::End(e)))
+                if (Ns::of(&ns), e.local_name().as_ref()) == (Ns::Atom, b"author") =>
+            {
+                return Ok(None)
+            }
+            Ok((_, Event::Eof)) => return Err("Error not find author end element".into()),
+            Err(e) => return Err(xml_error(reader, e)),
+            _ => (),
+        }
+        buf.clear();
+    }
+}
+
+// Function to handle parsing entry from xml
+fn parse_entry<R: BufRead>(
+    reader: &mut NsReader<R>,
+    entry_op: Option<EntryOptional>,
+) -> Result<Option<EntryOptional>> {
+    let mut buf = Vec::new();
+    let Some(entry) = entry_op else {
+        return Ok(None);
+    };
+
+    match reader.read_resolved_event_into(&mut buf) {
+        Ok((ns, Event::Start(e))) => {
+            match (Ns::of(&ns), e.local_name().as_ref()) {
+                (Ns::YouTube, b"videoId") => {
+                    let video_id = read_text(reader, e.name())?;
+                    parse_entry(
+                        reader,
+                        Some(EntryOptional {
+                            video_id: Some(video_id),
+                            ..entry
+                        }),
+                    )
+                }
+                (Ns::YouTube, b"channelId") => {
+                    let channel_id = read_text(reader, e.name())?;
+                    parse_entry(
+                        reader,
+                        Some(EntryOptional {
+                            channel_id: Some(channel_id),
+                            ..entry
+                        }),
+                    )
+                }
+                (Ns::Atom, b"title") => {
+                    let title = read_text(reader, e.name())?;
+                    parse_entry(
+                        reader,
+                        Some(EntryOptional {
+                            title: Some(title),
+                            ..entry
+                        }),
+                    )
+                }
+                // author
+                (Ns::Atom, b"author") => {
+                    let author = parse_author(reader)?;
+                    parse_entry(reader, Some(EntryOptional { author, ..entry }))
+                }
+                (Ns::Atom, b"published") => {
+                    let published = read_timestamp(reader, e.name())?;
+                    parse_entry(reader, Some(EntryOptional { published, ..entry }))
+                }
+                (Ns::Atom, b"updated") => {
+                    let updated = read_timestamp(reader, e.name())?;
+                    parse_entry(reader, Some(EntryOptional { updated, ..entry }))
+                }
+                (Ns::Media, b"description") => {
+                    let description = read_text(reader, e.name())?;
+                    parse_entry(
+                        reader,
+                        Some(EntryOptional {
+                            description: Some(description),
+                            ..entry
+                        }),
+                    )
+                }
+                (Ns::Atom, b"id") => {
+                    let id = read_text(reader, e.name())?;
+                    parse_entry(
+                        reader,
+                        Some(EntryOptional {
+                            id: Some(id),
+                            ..entry
+                        }),
+                    )
+                }
+                // Generic atom feeds carry a summary instead of media:description
+                (Ns::Atom, b"summary") => {
+                    let summary = read_text(reader, e.name())?;
+                    parse_entry(
+                        reader,
+                        Some(EntryOptional {
+                            description: entry.description.or(Some(summary)),
+                            ..entry
+                        }),
+                    )
+                }
+                _ => parse_entry(reader, Some(entry)),
+            }
+        }
+        // Links and thumbnails are only read for classifying the entry, view
+        // counts are kept. A link without rel is an alternate link.
+        Ok((ns, Event::Empty(e))) => match (Ns::of(&ns), e.local_name().as_ref()) {
+            (Ns::Atom, b"link")
+                if attribute(reader, &e, b"rel")?
+                    .as_deref()
+                    .is_none_or(|rel| rel == "alternate") =>
+            {
+                let link = attribute(reader, &e, b"href")?;
+                parse_entry(reader, Some(EntryOptional { link, ..entry }))
+            }
+            (Ns::Media, b"thumbnail") => {
+                let width = attribute(reader, &e, b"width")?.and_then(|w| w.parse().ok());
+                let height = attribute(reader, &e, b"height")?.and_then(|h| h.parse().ok());
+                parse_entry(
+                    reader,
+                    Some(EntryOptional {
+                        thumbnail: width.zip(height),
+                        ..entry
+                    }),
+                )
+            }
+            (Ns::Media, b"statistics") => {
+                let views = attribute(reader, &e, b"views")?.and_then(|v| v.parse().ok());
+                parse_entry(reader, Some(EntryOptional { views, ..entry }))
+            }
+            _ => parse_entry(reader, Some(entry)),
+        },
+        Ok((ns, Event::End(e))) => match (Ns::of(&ns), e.local_name().as_ref()) {
+            (Ns::Atom, b"entry") => Ok(Some(entry)),
+            _ => parse_entry(reader, Some(entry)),
+        },
+        Ok((_, Event::Eof)) => Err("Error not find entry end element".into()),
+        Err(e) => Err(xml_error(reader, e)),
+        _ => parse_entry(reader, Some(entry)),
+    }
+}
+
+// Value of an unprefixed attribute, unescaped
+fn attribute<R>(reader: &NsReader<R>, e: &BytesStart, key: &[u8]) -> Result<Option<String>> {
+    for attr in e.attributes().flatten() {
+        if attr.key.as_ref() == key {
+            let value = attr.unescape_value().map_err(|e| xml_error(reader, e))?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+
+    Ok(None)
+}
+
+// Pull the channel id out of a channel uri like https://www.youtube.com/channel/UC...
+fn channel_id_from_uri(uri: &str) -> Option<String> {
+    uri.rsplit_once("/channel/").map(|(_, id)| id.to_string())
+}
+
+// Function to handle parsing deleted entry from xml
+// <at:deleted-entry ref="yt:video:ID" when="..."> optionally carries <at:by> with the channel uri
+fn parse_deleted_entry<R: BufRead>(
+    reader: &mut NsReader<R>,
+    start: &BytesStart,
+    empty: bool,
+) -> Result<Option<Tombstone>> {
+    let mut video_id = None;
+    let mut deleted = None;
+    for attr in start.attributes().flatten() {
+        let value = attr
+            .unescape_value()
+            .map_err(|e| xml_error(reader, e))?
+            .into_owned();
+        match attr.key.as_ref() {
+            b"ref" => video_id = Some(value.trim_start_matches("yt:video:").to_string()),
+            b"when" => deleted = Some(value),
+            _ => (),
+        }
+    }
+
+    let mut channel_id = None;
+    let mut buf = Vec::new();
+    if !empty {
+        loop {
+            match reader.read_resolved_event_into(&mut buf) {
+                Ok((ns, Event::Start(e)))
+                    if (Ns::of(&ns), e.local_name().as_ref()) == (Ns::Atom, b"uri") =>
+                {
+                    let uri = read_text(reader, e.name())?;
+                    channel_id = channel_id_from_uri(&uri);
+                }
+                Ok((ns, Event::End(e)))
+                    if (Ns::of(&ns), e.local_name().as_ref())
+                        == (Ns::Tombstones, b"deleted-entry") =>
+                {
+                    break
+                }
+                Ok((_, Event::Eof)) => {
+                    return Err("Error not find at:deleted-entry end element".into())
+                }
+                Err(e) => return Err(xml_error(reader, e)),
+                _ => (),
+            }
+            buf.clear();
+        }
+    }
+
+    // A deletion with no video or no time to it can not be recorded
+    match (video_id, deleted) {
+        (Some(video_id), Some(deleted)) => Ok(Some(Tombstone {
+            video_id,
+            channel_id,
+            deleted,
+        })),
+        (video_id, _) => {
+            warn!(video_id = ?video_id, "skipping deleted entry, it needs both ref and when");
+            Ok(None)
+        }
+    }
+}
+
+// Function to handle parsing an rss 2.0 item into the fields of an atom entry.
+// The guid, else the link, stands in for the video id.
+fn parse_item<R: BufRead>(reader: &mut NsReader<R>) -> Result<EntryOptional> {
+    let mut item = NULL_ENTRY;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_resolved_event_into(&mut buf) {
+            Ok((ns, Event::Start(e))) => match (Ns::of(&ns), e.local_name().as_ref()) {
+                (Ns::Atom, b"guid") => item.id = Some(read_text(reader, e.name())?),
+                (Ns::Atom, b"title") => item.title = Some(read_text(reader, e.name())?),
+                (Ns::Atom, b"link") => item.link = Some(read_text(reader, e.name())?),
+                (Ns::Atom, b"description") => item.description = Some(read_text(reader, e.name())?),
+                (Ns::Atom, b"pubDate") => {
+                    let date = read_pub_date(reader, e.name())?;
+                    item.published = date.or(item.published);
+                }
+                (Ns::DublinCore, b"date") => {
+                    let date = read_timestamp(reader, e.name())?;
+                    item.published = item.published.or(date);
+                }
+                // author is meant to be an email address, a name is preferred
+                (Ns::Atom, b"author") => {
+                    let author = read_text(reader, e.name())?;
+                    item.author = item.author.or(Some(author));
+                }
+                (Ns::DublinCore, b"creator") | (Ns::Itunes, b"author") => {
+                    item.author = Some(read_text(reader, e.name())?)
+                }
+                _ => (),
+            },
+            Ok((ns, Event::End(e)))
+                if (Ns::of(&ns), e.local_name().as_ref()) == (Ns::Atom, b"item") =>
+            {
+                return Ok(item)
+            }
+            Ok((_, Event::Eof)) => return Err("Error not find item end element".into()),
+            Err(e) => return Err(xml_error(reader, e)),
+            _ => (),
+        }
+        buf.clear();
+    }
+}
+
+// What a feed says about itself, for entries that leave it out
+struct Header {
+    feed: FeedType,
+    // atom:id of the feed
+    id: Option<String>,
+    // yt:channelId of the feed
+    channel_id: Option<String>,
+    title: Option<String>,
+    // Website of an rss channel
+    link: Option<String>,
+    author: Option<String>,
+    // Set by the yt:playlistId of a playlist feed, entries then count positions
+    playlist: Option<String>,
+    // Start and end tag of the root element, the raw xml of an incomplete entry
+    // is wrapped in them so the prefixes it uses stay bound
+    root: Option<(String, String)>,
+}
+
+impl Header {
+    // Documents without a recognised root are read as youtube feeds, as they always were
+    fn new() -> Header {
+        Header {
+            feed: FeedType::YouTube,
+            id: None,
+            channel_id: None,
+            title: None,
+            link: None,
+            author: None,
+            playlist: None,
+            root: None,
+        }
+    }
+
+    // The channel's own title, from a youtube channel feed; playlist feeds are
+    // not a channel's own
+    fn channel(&self) -> Option<Channel> {
+        match (self.feed, &self.channel_id, &self.title, &self.playlist) {
+            (FeedType::YouTube, Some(channel_id), Some(title), None) => Some(Channel {
+                channel_id: channel_id.clone(),
+                feed_title: title.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    // Youtube entries are taken as they are, so one missing its video id stays
+    // incomplete. Entries of other feeds fall back on their own id and the feed's.
+    fn fill(&self, entry: &mut EntryOptional) {
+        match self.feed {
+            FeedType::YouTube => entry.kind = Some(kind::classify(entry)),
+            FeedType::Atom | FeedType::Rss => {
+                entry.feed = Some(self.feed);
+                entry.video_id = entry
+                    .video_id
+                    .take()
+                    .or_else(|| entry.id.clone())
+                    .or_else(|| entry.link.clone());
+                entry.channel_id = entry
+                    .channel_id
+                    .take()
+                    .or_else(|| self.channel_id.clone())
+                    .or_else(|| self.id.clone())
+                    .or_else(|| self.link.clone());
+                entry.author = entry
+                    .author
+                    .take()
+                    .or_else(|| self.author.clone())
+                    .or_else(|| self.title.clone());
+                entry.published = entry.published.or(entry.updated);
+            }
+        }
+    }
+}
+
+// The root element tells the kind of feed: rss, or an atom feed that binds the
+// youtube namespace or not
+fn feed_type(e: &BytesStart) -> FeedType {
+    if e.local_name().as_ref() == b"rss" {
+        return FeedType::Rss;
+    }
+    let youtube = e
+        .attributes()
+        .flatten()
+        .any(|attr| attr.value.as_ref() == YOUTUBE);
+    match youtube {
+        true => FeedType::YouTube,
+        false => FeedType::Atom,
+    }
+}
+
+// What parse hands back for every entry and tombstone
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Parsed {
+    Record(Record),
+    // An entry missing some of its fields, with the raw xml of the entry
+    Incomplete(EntryOptional, String),
+}
+
+// Keeps a copy of what the xml reader consumes since the last clear, so the raw
+// text of an entry is at hand once it turns out to be incomplete
+struct Recorder<R> {
+    inner: R,
+    recorded: Vec<u8>,
+}
+
+impl<R: BufRead> Read for Recorder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Recorder<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    // Whatever is consumed was just returned by fill_buf, so asking again does no io
+    fn consume(&mut self, amt: usize) {
+        if amt > 0 {
+            if let Ok(available) = self.inner.fill_buf() {
+                self.recorded.extend_from_slice(&available[..amt]);
+            }
+        }
+        self.inner.consume(amt);
+    }
+}
+
+// Function to handle parsing xml, calling emit with each record as its element ends.
+// Only one entry is held at a time, so memory stays flat however long the input is.
+pub fn parse_from<R: BufRead>(input: R, mut emit: impl FnMut(Parsed) -> Result<()>) -> Result<()> {
+    let mut reader = NsReader::from_reader(Recorder {
+        inner: input,
+        recorded: Vec::new(),
+    });
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    // Input can be several documents back to back, each gets a header and
+    // playlist positions of its own. Only the outermost feed or rss element
+    // starts a document.
+    let mut header = Header::new();
+    let mut feeds = 0;
+    let mut position = 0;
+
+    // The `Reader` does not implement `Iterator` because it outputs borrowed data (`Cow`s)
+    loop {
+        reader.get_mut().recorded.clear();
+        match reader.read_resolved_event_into(&mut buf) {
+            Err(e) => return Err(xml_error(&reader, e)),
+            // exits the loop when reaching end of file
+            Ok((_, Event::Eof)) => break,
+
+            Ok((ns, Event::Start(e))) => match (Ns::of(&ns), e.local_name().as_ref()) {
+                (Ns::Atom, b"feed" | b"rss") => {
+                    if feeds == 0 {
+                        header = Header::new();
+                        header.feed = feed_type(&e);
+                        header.root = Some((
+                            format!("<{}>", String::from_utf8_lossy(&e)),
+                            format!("</{}>", String::from_utf8_lossy(e.name().as_ref())),
+                        ));
+                        position = 0;
+                    }
+                    feeds += 1;
+                }
+                (Ns::YouTube, b"playlistId") => {
+                    header.playlist = Some(read_text(&mut reader, e.name())?);
+                }
+                (Ns::YouTube, b"channelId") => {
+                    header.channel_id = Some(read_text(&mut reader, e.name())?);
+                }
+                (Ns::Atom, b"id") => header.id = Some(read_text(&mut reader, e.name())?),
+                (Ns::Atom, b"title") => header.title = Some(read_text(&mut reader, e.name())?),
+                (Ns::Atom, b"link") => header.link = Some(read_text(&mut reader, e.name())?),
+                (Ns::Atom, b"author") if header.feed != FeedType::Rss => {
+                    header.author = parse_author(&mut reader)?;
+                }
+                (Ns::DublinCore, b"creator") | (Ns::Itunes, b"author") => {
+                    header.author = Some(read_text(&mut reader, e.name())?);
+                }
+                // Their titles and links are not the channel's
+                (Ns::Atom, b"image" | b"textInput") | (Ns::Itunes, b"owner") => {
+                    reader
+                        .read_to_end_into(e.name(), &mut Vec::new())
+                        .map_err(|e| xml_error(&reader, e))?;
+                }
+                (Ns::Atom, b"entry") => {
+                    if let Some(mut entry) = parse_entry(&mut reader, Some(NULL_ENTRY))? {
+                        header.fill(&mut entry);
+                        if let Some((playlist_id, video_id)) =
+                            header.playlist.as_ref().zip(entry.video_id.as_ref())
+                        {
+                            emit(Parsed::Record(Record::Membership(Membership {
+                                playlist_id: playlist_id.clone(),
+                                video_id: video_id.clone(),
+                                position,
+                            })))?;
+                        }
+                        position += 1;
+                        emit_entry(&reader, &header, entry, &mut emit)?;
+                    }
+                }
+                (Ns::Atom, b"item") if header.feed == FeedType::Rss => {
+                    let mut item = parse_item(&mut reader)?;
+                    header.fill(&mut item);
+                    emit_entry(&reader, &header, item, &mut emit)?;
+                }
+                (Ns::Tombstones, b"deleted-entry") => {
+                    if let Some(tombstone) = parse_deleted_entry(&mut reader, &e, false)? {
+                        emit(Parsed::Record(Record::Tombstone(tombstone)))?;
+                    }
+                }
+                _ => (),
+            },
+            Ok((ns, Event::End(e)))
+                if Ns::of(&ns) == Ns::Atom
+                    && matches!(e.local_name().as_ref(), b"feed" | b"rss") =>
+            {
+                feeds -= 1;
+                if feeds == 0 {
+                    if let Some(channel) = header.channel() {
+                        emit(Parsed::Record(Record::Channel(channel)))?;
+                    }
+                    header = Header::new();
+                    position = 0;
+                }
+            }
+            Ok((ns, Event::Empty(e)))
+                if (Ns::of(&ns), e.local_name().as_ref()) == (Ns::Tombstones, b"deleted-entry") =>
+            {
+                if let Some(tombstone) = parse_deleted_entry(&mut reader, &e, true)? {
+                    emit(Parsed::Record(Record::Tombstone(tombstone)))?;
+                }
+            }
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    Ok(())
+}
+
+// An entry as a record when it is complete, with its raw xml otherwise. The raw
+// xml is a document of its own, the entry inside the root element it came in.
+fn emit_entry<R: BufRead>(
+    reader: &NsReader<Recorder<R>>,
+    header: &Header,
+    entry: EntryOptional,
+    emit: &mut impl FnMut(Parsed) -> Result<()>,
+) -> Result<()> {
+    match entry.into_entry() {
+        Ok(entry) => emit(Parsed::Record(Record::Entry(entry))),
+        Err(partial) => {
+            // Whitespace before the entry is recorded too
+            let raw = String::from_utf8_lossy(&reader.get_ref().recorded);
+            let raw = raw.trim_start();
+            let raw = match &header.root {
+                Some((start, end)) => format!("{}\n{}\n{}\n", start, raw, end),
+                None => raw.to_string(),
+            };
+            emit(Parsed::Incomplete(*partial, raw))
+        }
+    }
+}
+
+// Everything parse_from finds in a feed document already in memory
+pub fn parse(xml: &str) -> Result<Vec<Parsed>> {
+    let mut parsed = Vec::new();
+    parse_from(xml.as_bytes(), |item| {
+        parsed.push(item);
+        Ok(())
+    })?;
+
+    Ok(parsed)
+}
+
+// Complete records of a feed document, for tests in this and other modules
+#[cfg(test)]
+pub fn records(xml: &str) -> Vec<Record> {
+    parse(xml)
+        .unwrap()
+        .into_iter()
+        .filter_map(|parsed| match parsed {
+            Parsed::Record(record) => Some(record),
+            Parsed::Incomplete(..) => None,
+        })
+        .collect()
+}
+
+// Entries of a feed document, for tests in this and other modules
+#[cfg(test)]
+pub fn entries(xml: &str) -> Vec<crate::entry::Entry> {
+    records(xml)
+        .into_iter()
+        .filter_map(|record| match record {
+            Record::Entry(entry) => Some(entry),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tombstone_with_channel() {
+        let xml = r#"<feed xmlns:at="http://purl.org/atompub/tombstones/1.0" xmlns="http://www.w3.org/2005/Atom">
+ <at:deleted-entry ref="yt:video:Ab3CdEfGhIj" when="2022-11-06T10:00:00+00:00">
+  <link href="https://www.youtube.com/watch?v=Ab3CdEfGhIj"/>
+  <at:by>
+   <name>Dude Perfect</name>
+   <uri>https://www.youtube.com/channel/UCRijo3ddMTht_IHyNSNXpNQ</uri>
+  </at:by>
+ </at:deleted-entry>
+</feed>"#;
+        assert_eq!(
+            parse(xml).unwrap(),
+            [Parsed::Record(Record::Tombstone(Tombstone {
+                video_id: "Ab3CdEfGhIj".to_string(),
+                channel_id: Some("UCRijo3ddMTht_IHyNSNXpNQ".to_string()),
+                deleted: "2022-11-06T10:00:00+00:00".to_string(),
+            }))]
+        );
+    }
+
+    #[test]
+    fn tombstone_without_channel() {
+        let xml = r#"<at:deleted-entry xmlns:at="http://purl.org/atompub/tombstones/1.0" ref="yt:video:Ab3CdEfGhIj" when="2022-11-06T10:00:00+00:00"/>"#;
+        assert_eq!(
+            parse(xml).unwrap(),
+            [Parsed::Record(Record::Tombstone(Tombstone {
+                video_id: "Ab3CdEfGhIj".to_string(),
+                channel_id: None,
+                deleted: "2022-11-06T10:00:00+00:00".to_string(),
+            }))]
+        );
+    }
+
+    #[test]
+    fn tombstone_needs_ref_and_when() {
+        assert_eq!(
+            parse(r#"<at:deleted-entry xmlns:at="http://purl.org/atompub/tombstones/1.0" ref="yt:video:Ab3CdEfGhIj"/>"#).unwrap(),
+            []
+        );
+        assert_eq!(
+            parse(r#"<at:deleted-entry xmlns:at="http://purl.org/atompub/tombstones/1.0" when="2022-11-06T10:00:00+00:00"/>"#).unwrap(),
+            []
+        );
+    }
+
+    #[test]
+    fn channel_feeds_record_their_title() {
+        let xml = r#"<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns="http://www.w3.org/2005/Atom">
+ <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+ <title>Dude Perfect</title>
+ <entry>
+  <yt:videoId>Ab3CdEfGhIj</yt:videoId>
+  <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+  <title>Overtime 30</title>
+  <author><name>Dude Perfect</name></author>
+  <published>2022-11-05T13:59:57+00:00</published>
+ </entry>
+</feed>"#;
+        let parsed = parse(xml).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(
+            parsed[1],
+            Parsed::Record(Record::Channel(Channel {
+                channel_id: "UCRijo3ddMTht_IHyNSNXpNQ".to_string(),
+                feed_title: "Dude Perfect".to_string(),
+            }))
+        );
+
+        // A playlist feed is titled by the playlist, not the channel
+        let playlist = xml.replace(
+            " <title>Dude Perfect</title>",
+            " <yt:playlistId>PLxyz</yt:playlistId>\n <title>Trick Shots</title>",
+        );
+        assert!(parse(&playlist)
+            .unwrap()
+            .iter()
+            .all(|parsed| !matches!(parsed, Parsed::Record(Record::Channel(_)))));
+    }
+
+    // What the whole-string parser wrote before feeds were streamed
+    #[test]
+    fn text_is_kept_as_written() {
+        let xml = r#"<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns="http://www.w3.org/2005/Atom">
+ <entry>
+  <yt:videoId>TrimCheck01</yt:videoId>
+  <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+  <title>  Tom &amp; Jerry <!-- c --> part   </title>
+  <author><name> Dude Perfect </name></author>
+  <published>2022-11-05T13:59:57+00:00</published>
+ </entry>
+</feed>"#;
+        let entries = entries(xml);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "  Tom &amp; Jerry <!-- c --> part   ");
+        assert_eq!(entries[0].author, " Dude Perfect ");
+    }
+
+    #[test]
+    fn markup_inside_text_is_written_back() {
+        let xml = r#"<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns="http://www.w3.org/2005/Atom">
+ <entry>
+  <yt:videoId>TrimCheck02</yt:videoId>
+  <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+  <title>a <![CDATA[<b>]]> <br/> <title>nested</title> <?pi x?> z</title>
+  <author><name>Dude Perfect</name></author>
+  <published>2022-11-05T13:59:57+00:00</published>
+ </entry>
+</feed>"#;
+        assert_eq!(
+            entries(xml)[0].title,
+            "a <![CDATA[<b>]]> <br/> <title>nested</title> <?pi x?> z"
+        );
+    }
+
+    #[test]
+    fn alternate_prefixes_resolve_by_namespace() {
+        let xml = include_str!("../data/src/alternate-prefixes.xml");
+        let entries = entries(xml);
+        assert_eq!(entries.len(), 2);
+
+        // a: for atom and youtube: for the youtube namespace
+        assert_eq!(entries[0].video_id, "YXXlSG-du7c");
+        assert_eq!(entries[0].channel_id, "UCRijo3ddMTht_IHyNSNXpNQ");
+        assert_eq!(entries[0].author, "Dude Perfect");
+        assert_eq!(
+            entries[0].published.to_string(),
+            "2022-11-05T13:59:57+00:00"
+        );
+        // v: declared on the entry itself, atom as its default namespace
+        assert_eq!(entries[1].video_id, "rH0mlVbVCvw");
+        assert_eq!(entries[1].channel_id, "UCRijo3ddMTht_IHyNSNXpNQ");
+
+        let parsed = parse(xml).unwrap();
+        let tombstones: Vec<&Tombstone> = parsed
+            .iter()
+            .filter_map(|parsed| match parsed {
+                Parsed::Record(Record::Tombstone(tombstone)) => Some(tombstone),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tombstones.len(), 1);
+        assert_eq!(tombstones[0].video_id, "Ab3CdEfGhIj");
+        assert_eq!(
+            tombstones[0].channel_id.as_deref(),
+            Some("UCRijo3ddMTht_IHyNSNXpNQ")
+        );
+        assert_eq!(
+            parsed.last(),
+            Some(&Parsed::Record(Record::Channel(Channel {
+                channel_id: "UCRijo3ddMTht_IHyNSNXpNQ".to_string(),
+                feed_title: "Dude Perfect".to_string(),
+            })))
+        );
+    }
+
+    #[test]
+    fn media_title_is_not_the_entry_title() {
+        let entries = entries(include_str!("../data/src/alternate-prefixes.xml"));
+        assert_eq!(entries[0].title, "Dude Perfect Goes to SPACE");
+        assert_eq!(entries[1].title, "Overtime 30");
+    }
+
+    #[test]
+    fn incomplete_entry_raw_xml_parses_again() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns:media="http://search.yahoo.com/mrss/" xmlns="http://www.w3.org/2005/Atom">
+ <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+ <entry>
+  <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+  <title>No video id</title>
+  <media:group>
+   <media:description>Lost its yt:videoId</media:description>
+  </media:group>
+ </entry>
+</feed>"#;
+        let parsed = parse(xml).unwrap();
+        let [Parsed::Incomplete(partial, raw)] = parsed.as_slice() else {
+            panic!("expected one incomplete entry, got {:?}", parsed);
+        };
+        assert!(raw.starts_with("<feed xmlns:yt="));
+        assert!(raw.trim_end().ends_with("</feed>"));
+
+        let again = parse(raw).unwrap();
+        let [Parsed::Incomplete(partial_again, _)] = again.as_slice() else {
+            panic!("expected the raw xml to hold the entry, got {:?}", again);
+        };
+        assert_eq!(partial_again, partial);
+        assert_eq!(
+            partial.channel_id.as_deref(),
+            Some("UCRijo3ddMTht_IHyNSNXpNQ")
+        );
+    }
+
+    #[test]
+    fn bad_dates_leave_the_entry_incomplete() {
+        let xml = r#"<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns="http://www.w3.org/2005/Atom">
+ <entry>
+  <yt:videoId>Ab3CdEfGhIj</yt:videoId>
+  <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+  <title>Bad date</title>
+  <author><name>Dude Perfect</name></author>
+  <published>last tuesday</published>
+ </entry>
+ <entry>
+  <yt:videoId>Kl4MnOpQrSt</yt:videoId>
+  <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+  <title>Good date</title>
+  <author><name>Dude Perfect</name></author>
+  <published>2022-11-05T13:59:57+00:00</published>
+ </entry>
+</feed>"#;
+        let parsed = parse(xml).unwrap();
+        assert_eq!(parsed.len(), 2);
+        let Parsed::Incomplete(partial, _) = &parsed[0] else {
+            panic!("expected an incomplete entry, got {:?}", parsed[0]);
+        };
+        assert_eq!(partial.video_id.as_deref(), Some("Ab3CdEfGhIj"));
+        assert_eq!(partial.published, None);
+        assert!(
+            matches!(&parsed[1], Parsed::Record(Record::Entry(entry)) if entry.video_id == "Kl4MnOpQrSt")
+        );
+    }
+
+    fn memberships(parsed: &[Parsed]) -> Vec<&Membership> {
+        parsed
+            .iter()
+            .filter_map(|parsed| match parsed {
+                Parsed::Record(Record::Membership(membership)) => Some(membership),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn playlist_feed_records_positions() {
+        let parsed = parse(include_str!("../data/src/playlist.xml")).unwrap();
+        let memberships = memberships(&parsed);
+        assert_eq!(memberships.len(), 2);
+        assert!(memberships
+            .iter()
+            .all(|m| m.playlist_id == "PLcirGkCPmbmFeQ1sm4wFciF03D_EroIfr"));
+        assert_eq!(memberships[0].position, 0);
+        assert_eq!(memberships[1].position, 1);
+        // A playlist is not the channel's own feed
+        assert!(!parsed
+            .iter()
+            .any(|parsed| matches!(parsed, Parsed::Record(Record::Channel(_)))));
+    }
+
+    // What stdin hands over when feed files are piped in one after another
+    #[test]
+    fn documents_back_to_back_have_their_own_header() {
+        let playlist = include_str!("../data/src/playlist.xml");
+        let channel = include_str!("../data/src/template.xml");
+
+        let parsed = parse(&format!("{}{}", playlist, channel)).unwrap();
+        assert_eq!(memberships(&parsed).len(), 2);
+        assert_eq!(
+            entries(&format!("{}{}", playlist, channel)).len(),
+            entries(playlist).len() + entries(channel).len()
+        );
+        assert!(matches!(
+            parsed.last(),
+            Some(Parsed::Record(Record::Channel(_)))
+        ));
+
+        // and the other way round, positions start again from 0
+        let parsed = parse(&format!("{}{}", channel, playlist)).unwrap();
+        let positions: Vec<u32> = memberships(&parsed).iter().map(|m| m.position).collect();
+        assert_eq!(positions, [0, 1]);
+    }
+
+    #[test]
+    fn atom_entries_fall_back_on_their_own_ids() {
+        let entries = entries(include_str!("../data/src/atom.xml"));
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.feed == Some(FeedType::Atom)));
+
+        assert_eq!(
+            entries[0].video_id,
+            "urn:uuid:1225c695-cfb8-4ebb-aaaa-80da344efa6a"
+        );
+        assert_eq!(
+            entries[0].channel_id,
+            "urn:uuid:60a76c80-d399-11d9-b93C-0003939e0af6"
+        );
+        assert_eq!(entries[0].author, "Ty Toney");
+        assert_eq!(
+            entries[0].published.to_string(),
+            "2022-11-07T09:00:00+01:00"
+        );
+        assert_eq!(
+            entries[0].description.as_deref(),
+            Some("How the shot came together")
+        );
+        // No published date, updated stands in
+        assert_eq!(entries[1].author, "Garrett Hilbert");
+        assert_eq!(
+            entries[1].published.to_string(),
+            "2022-11-02T12:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn rss_items_read_as_entries() {
+        let xml = include_str!("../data/src/podcast.xml");
+        let entries = entries(xml);
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.feed == Some(FeedType::Rss)));
+
+        assert_eq!(entries[0].video_id, "trickshot-episode-12");
+        assert_eq!(
+            entries[0].channel_id,
+            "https://podcast.example.com/trickshot"
+        );
+        assert_eq!(entries[0].title, "Episode 12: Bottle flips");
+        assert_eq!(entries[0].author, "Cody Jones");
+        assert_eq!(
+            entries[0].published.to_string(),
+            "2022-11-08T18:00:00+00:00"
+        );
+        // No guid, the link stands in; no creator, the channel's itunes:author
+        assert_eq!(
+            entries[1].video_id,
+            "https://podcast.example.com/trickshot/11"
+        );
+        assert_eq!(entries[1].author, "Trick Shot Talk Crew");
+        assert_eq!(
+            entries[1].published.to_string(),
+            "2022-11-01T13:00:00-05:00"
+        );
+
+        // The item with neither title nor date
+        let parsed = parse(xml).unwrap();
+        assert!(matches!(parsed.last(), Some(Parsed::Incomplete(..))));
+    }
+
+    // Each document's root decides its own feed type
+    #[test]
+    fn feed_type_is_read_for_every_document() {
+        let channel = include_str!("../data/src/template.xml");
+        let youtube = entries(channel).len();
+        for (other, feed) in [
+            (include_str!("../data/src/podcast.xml"), FeedType::Rss),
+            (include_str!("../data/src/atom.xml"), FeedType::Atom),
+        ] {
+            let entries = entries(&format!("{}{}", channel, other));
+            assert!(entries[..youtube].iter().all(|entry| entry.is_youtube()));
+            assert_eq!(entries.len(), youtube + 2);
+            assert!(entries[youtube..]
+                .iter()
+                .all(|entry| entry.feed == Some(feed)));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Files done, entries per second and time left while a parse run is going
+ *
+*/
+
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+// How often the terminal line is redrawn
+const REDRAW: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressMode {
+    // A redrawn line when stderr is a terminal, log lines otherwise
+    #[default]
+    Auto,
+    Bar,
+    Log,
+    Off,
+}
+
+impl std::str::FromStr for ProgressMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<ProgressMode, String> {
+        <ProgressMode as ValueEnum>::from_str(s, true)
+    }
+}
+
+// Counters bumped from the rayon workers, read by the reporting thread
+pub struct Progress {
+    total: usize,
+    started: Instant,
+    files: AtomicUsize,
+    entries: AtomicUsize,
+    finished: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl Progress {
+    fn new(total: usize) -> Progress {
+        Progress {
+            total,
+            started: Instant::now(),
+            files: AtomicUsize::new(0),
+            entries: AtomicUsize::new(0),
+            finished: Mutex::new(false),
+            wake: Condvar::new(),
+        }
+    }
+
+    pub fn file_done(&self, entries: usize) {
+        self.entries.fetch_add(entries, Ordering::Relaxed);
+        self.files.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Run f while a second thread reports on it every interval
+    pub fn run<T>(
+        mode: ProgressMode,
+        interval: Duration,
+        total: usize,
+        f: impl FnOnce(&Progress) -> T,
+    ) -> T {
+        let progress = Progress::new(total);
+        let bar = match mode {
+            ProgressMode::Off => return f(&progress),
+            ProgressMode::Auto => std::io::stderr().is_terminal(),
+            ProgressMode::Bar => true,
+            ProgressMode::Log => false,
+        };
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| progress.report(bar, if bar { REDRAW } else { interval }));
+            let result = f(&progress);
+            *progress.finished.lock().unwrap() = true;
+            progress.wake.notify_all();
+            result
+        })
+    }
+
+    fn report(&self, bar: bool, interval: Duration) {
+        let mut finished = self.finished.lock().unwrap();
+        while !*finished {
+            finished = self.wake.wait_timeout(finished, interval).unwrap().0;
+            if *finished {
+                break;
+            }
+            if bar {
+                self.draw();
+            } else {
+                self.log();
+            }
+        }
+
+        if bar {
+            self.draw();
+            eprintln!();
+        }
+    }
+
+    // Snapshot as (files done, entries per second, seconds left)
+    fn rates(&self) -> (usize, f64, Option<u64>) {
+        let files = self.files.load(Ordering::Relaxed);
+        let entries = self.entries.load(Ordering::Relaxed);
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let per_sec = if elapsed > 0.0 {
+            entries as f64 / elapsed
+        } else {
+            0.0
+        };
+        // Time left assumes the remaining files take as long as the ones so far
+        let eta = (files > 0).then(|| {
+            let left = self.total.saturating_sub(files) as f64;
+            (elapsed / files as f64 * left).round() as u64
+        });
+
+        (files, per_sec, eta)
+    }
+
+    fn draw(&self) {
+        let (files, per_sec, eta) = self.rates();
+        let eta = eta.map_or_else(|| "?".to_string(), format_secs);
+        let mut stderr = std::io::stderr().lock();
+        // \x1b[K clears what is left of a longer previous line
+        let _ = write!(
+            stderr,
+            "\r{}/{} files, {:.0} entries/s, eta {}\x1b[K",
+            files, self.total, per_sec, eta
+        );
+        let _ = stderr.flush();
+    }
+
+    fn log(&self) {
+        let (files, per_sec, eta) = self.rates();
+        info!(
+            files_done = files,
+            files_total = self.total,
+            entries_per_sec = per_sec.round() as u64,
+            eta_secs = eta,
+            "progress"
+        );
+    }
+}
+
+fn format_secs(secs: u64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secs_as_clock() {
+        assert_eq!(format_secs(0), "0:00:00");
+        assert_eq!(format_secs(59), "0:00:59");
+        assert_eq!(format_secs(3 * 3600 + 4 * 60 + 5), "3:04:05");
+    }
+
+    #[test]
+    fn eta_follows_files_done() {
+        let progress = Progress::new(4);
+        assert_eq!(progress.rates().2, None);
+
+        progress.file_done(10);
+        std::thread::sleep(Duration::from_millis(20));
+        let (files, per_sec, eta) = progress.rates();
+        assert_eq!(files, 1);
+        assert!(per_sec > 0.0);
+        assert!(eta.is_some());
+
+        progress.file_done(10);
+        progress.file_done(10);
+        progress.file_done(10);
+        assert_eq!(progress.rates().2, Some(0));
+    }
+
+    #[test]
+    fn off_runs_without_a_reporter() {
+        let done = Progress::run(ProgressMode::Off, Duration::from_secs(1), 2, |progress| {
+            progress.file_done(1);
+            progress.file_done(1);
+            progress.files.load(Ordering::Relaxed)
+        });
+        assert_eq!(done, 2);
+    }
+}
@@ -0,0 +1,45 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Where parsed records go: the cache directory or stdout
+ *
+*/
+
+use std::io::Write;
+
+use crate::cache::{Cache, Written};
+use crate::entry::Record;
+use crate::format::Format;
+use crate::Result;
+
+pub enum Sink {
+    Cache(Cache),
+    // Records written back to back on stdout, json lines or capnp messages
+    Stdout(Format),
+}
+
+impl Sink {
+    // Stdout can not tell duplicates apart, everything it gets is New
+    pub fn write(&self, record: &Record) -> Result<Written> {
+        match self {
+            Sink::Cache(cache) => cache.write(record),
+            Sink::Stdout(format) => {
+                let mut bytes = format.encode(record)?;
+                if *format == Format::Json {
+                    bytes.push(b'\n');
+                }
+
+                // One write under the lock keeps records from parallel parses whole
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(&bytes)?;
+                Ok(Written::New)
+            }
+        }
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        if let Sink::Stdout(_) = self {
+            std::io::stdout().lock().flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Reads feed documents out of plain, compressed and tar archived input files
+ *
+*/
+
+use std::io::{BufRead, BufReader, Chain, Cursor, Read};
+use std::path::Path;
+
+use crate::Result;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+// POSIX and GNU tar headers carry "ustar" at offset 257
+const TAR_MAGIC: &[u8] = b"ustar";
+const TAR_MAGIC_OFFSET: usize = 257;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+// Compression going by magic bytes, so misnamed files still decode
+pub fn sniff(bytes: &[u8]) -> Compression {
+    if bytes.starts_with(GZIP_MAGIC) {
+        Compression::Gzip
+    } else if bytes.starts_with(ZSTD_MAGIC) {
+        Compression::Zstd
+    } else {
+        Compression::None
+    }
+}
+
+// A stream with the bytes peeked from it put back in front
+type Peeked<R> = Chain<Cursor<Vec<u8>>, R>;
+
+// The first n bytes of a stream, and the stream still starting at them
+fn peek<R: Read>(mut input: R, n: usize) -> Result<(Vec<u8>, Peeked<R>)> {
+    let mut head = Vec::with_capacity(n);
+    input.by_ref().take(n as u64).read_to_end(&mut head)?;
+    Ok((head.clone(), Cursor::new(head).chain(input)))
+}
+
+// Wraps input in a decoder when its magic bytes say it is compressed
+pub fn decompress<'a>(input: impl Read + 'a) -> Result<Box<dyn Read + 'a>> {
+    let (head, input) = peek(input, ZSTD_MAGIC.len())?;
+    Ok(match sniff(&head) {
+        Compression::None => Box::new(input),
+        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(input)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(input)?),
+    })
+}
+
+fn is_tar(name: &str, head: &[u8]) -> bool {
+    let magic = head.get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + TAR_MAGIC.len());
+    magic == Some(TAR_MAGIC)
+        || [".tar", ".tar.gz", ".tgz", ".tar.zst", ".tzst"]
+            .iter()
+            .any(|ext| name.ends_with(ext))
+}
+
+// Tar members worth parsing; anything else in an archive is ignored
+fn is_feed_member(name: &str) -> bool {
+    [".xml", ".xml.gz", ".xml.zst"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+}
+
+// Call f with every feed document in an input stream: the stream itself, or each
+// xml member when it is a tar archive. Names of members are <file>!<member>.
+// Documents are handed over as readers and never held in memory whole.
+pub fn for_each_feed(
+    path: &Path,
+    input: impl Read,
+    mut f: impl FnMut(&str, &mut dyn BufRead) -> Result<()>,
+) -> Result<()> {
+    let name = path.display().to_string();
+    let input = decompress(input).map_err(|e| format!("decompressing {}: {}", name, e))?;
+    let (head, input) = peek(input, TAR_MAGIC_OFFSET + TAR_MAGIC.len())?;
+    if !is_tar(&name, &head) {
+        return f(&name, &mut BufReader::new(input));
+    }
+
+    let mut archive = tar::Archive::new(input);
+    for member in archive.entries()? {
+        let member = member?;
+        if !member.header().entry_type().is_file() {
+            continue;
+        }
+
+        let member_name = format!("{}!{}", name, member.path()?.display());
+        if !is_feed_member(&member_name) {
+            continue;
+        }
+
+        let member =
+            decompress(member).map_err(|e| format!("decompressing {}: {}", member_name, e))?;
+        f(&member_name, &mut BufReader::new(member))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const FEED: &str = "<feed><title>Dude Perfect</title></feed>";
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zstd(bytes: &[u8]) -> Vec<u8> {
+        zstd::stream::encode_all(bytes, 0).unwrap()
+    }
+
+    fn feeds(name: &str, bytes: &[u8]) -> Vec<(String, String)> {
+        let mut feeds = Vec::new();
+        for_each_feed(Path::new(name), bytes, |name, input| {
+            let mut xml = String::new();
+            input.read_to_string(&mut xml)?;
+            feeds.push((name.to_string(), xml));
+            Ok(())
+        })
+        .unwrap();
+        feeds
+    }
+
+    #[test]
+    fn compression_is_sniffed() {
+        assert_eq!(sniff(FEED.as_bytes()), Compression::None);
+        assert_eq!(sniff(&gzip(FEED.as_bytes())), Compression::Gzip);
+        assert_eq!(sniff(&zstd(FEED.as_bytes())), Compression::Zstd);
+
+        // Names don't matter, only the bytes
+        let expected = vec![("feed.xml".to_string(), FEED.to_string())];
+        assert_eq!(feeds("feed.xml", &gzip(FEED.as_bytes())), expected);
+        assert_eq!(feeds("feed.xml", &zstd(FEED.as_bytes())), expected);
+    }
+
+    #[test]
+    fn tar_members_are_feeds() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, bytes) in [
+            ("a.xml", FEED.as_bytes().to_vec()),
+            ("b.xml.gz", gzip(FEED.as_bytes())),
+            ("README", b"not a feed".to_vec()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, bytes.as_slice())
+                .unwrap();
+        }
+        let archive = builder.into_inner().unwrap();
+
+        // Sniffed by the ustar magic even when misnamed, then compressed as a whole
+        for (name, bytes) in [
+            ("feeds.bin", archive.clone()),
+            ("feeds.bin", zstd(&archive)),
+        ] {
+            assert_eq!(
+                feeds(name, &bytes),
+                [
+                    ("feeds.bin!a.xml".to_string(), FEED.to_string()),
+                    ("feeds.bin!b.xml.gz".to_string(), FEED.to_string()),
+                ]
+            );
+        }
+    }
+}
@@ -0,0 +1,197 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Finds paid promotions in titles and descriptions
+ *
+*/
+
+use regex_automata::meta::Regex;
+
+use crate::config::SponsorConfig;
+use crate::enrich::{self, contains_word, Link};
+use crate::Result;
+
+pub struct SponsorRules {
+    // Lowercased, matched as whole words
+    keywords: Vec<String>,
+    patterns: Vec<Regex>,
+    domains: Vec<String>,
+}
+
+// What the rules found in an entry
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Detected {
+    // Any rule matched, whether or not it named a sponsor
+    pub sponsored: bool,
+    pub sponsors: Vec<String>,
+}
+
+impl SponsorRules {
+    pub fn new(config: &SponsorConfig) -> Result<SponsorRules> {
+        let mut patterns = Vec::new();
+        for pattern in &config.patterns {
+            patterns.push(compile(pattern)?);
+        }
+
+        Ok(SponsorRules {
+            keywords: config.keywords.iter().map(|k| k.to_lowercase()).collect(),
+            patterns,
+            domains: config.domains.iter().map(|d| d.to_lowercase()).collect(),
+        })
+    }
+
+    // Keywords only mark an entry sponsored. Patterns name the sponsor with their
+    // first capture group when they have one, and a link to a sponsor domain
+    // names the domain.
+    pub fn detect(
+        &self,
+        title: Option<&str>,
+        description: Option<&str>,
+        links: &[Link],
+    ) -> Detected {
+        // Both are cached with their xml escapes, "Q&amp;A" is matched as "Q&A"
+        let text = [title, description]
+            .into_iter()
+            .flatten()
+            .map(enrich::unescape)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let lower = text.to_lowercase();
+
+        let mut detected = Detected {
+            sponsored: self.keywords.iter().any(|k| contains_word(&lower, k)),
+            sponsors: Vec::new(),
+        };
+        for pattern in &self.patterns {
+            for captures in pattern.captures_iter(text.as_str()) {
+                detected.sponsored = true;
+                if let Some(span) = captures.get_group(1) {
+                    detected.add(text[span.range()].trim());
+                }
+            }
+        }
+        for domain in &self.domains {
+            if links.iter().any(|link| link.is_to(domain)) {
+                detected.sponsored = true;
+                detected.add(domain);
+            }
+        }
+
+        detected
+    }
+}
+
+impl Detected {
+    // The same sponsor named twice, in any case, is kept once as first written
+    fn add(&mut self, sponsor: &str) {
+        let sponsor = sponsor.trim_end_matches(|c: char| c.is_ascii_punctuation());
+        if !sponsor.is_empty()
+            && !self
+                .sponsors
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(sponsor))
+        {
+            self.sponsors.push(sponsor.to_string());
+        }
+    }
+}
+
+pub fn compile(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| {
+        let reason = match e.syntax_error() {
+            Some(syntax) => syntax.to_string(),
+            None => e.to_string(),
+        };
+        format!("{:?} is not a valid regex: {}", pattern, reason).into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn rules() -> SponsorRules {
+        SponsorRules::new(&SponsorConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn patterns_name_the_sponsor() {
+        let detected = rules().detect(
+            Some("Overtime 30"),
+            Some("Thanks to Raid Shadow Legends for sponsoring this video!"),
+            &[],
+        );
+        assert_eq!(
+            detected,
+            Detected {
+                sponsored: true,
+                sponsors: vec!["Raid Shadow Legends".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn titles_are_unescaped() {
+        let detected = rules().detect(Some("Sponsored by AT&amp;T"), None, &[]);
+        assert_eq!(detected.sponsors, ["AT&T"]);
+    }
+
+    #[test]
+    fn keywords_only_mark_sponsored() {
+        let detected = rules().detect(None, Some("Use code DUDE at checkout"), &[]);
+        assert!(detected.sponsored);
+        assert!(detected.sponsors.is_empty());
+    }
+
+    #[test]
+    fn links_to_sponsor_domains() {
+        let rules = SponsorRules::new(&SponsorConfig {
+            domains: vec!["NordVPN.com".to_string()],
+            ..SponsorConfig::default()
+        })
+        .unwrap();
+        let links = [
+            Link::new("https://go.nordvpn.com/dude").unwrap(),
+            Link::new("https://store.dudeperfect.com/").unwrap(),
+        ];
+        let detected = rules.detect(Some("Overtime 30"), None, &links);
+        assert_eq!(
+            detected,
+            Detected {
+                sponsored: true,
+                sponsors: vec!["nordvpn.com".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn nothing_matched() {
+        let links = [Link::new("https://store.dudeperfect.com/").unwrap()];
+        let detected = rules().detect(
+            Some("Overtime 30"),
+            Some("New merch is out, sponsors of our little league team"),
+            &links,
+        );
+        assert_eq!(detected, Detected::default());
+    }
+
+    #[test]
+    fn bad_patterns_are_config_errors() {
+        let config = SponsorConfig {
+            patterns: vec!["(unclosed".to_string()],
+            ..SponsorConfig::default()
+        };
+        let error = SponsorRules::new(&config).err().unwrap().to_string();
+        assert!(
+            error.starts_with("\"(unclosed\" is not a valid regex: "),
+            "{}",
+            error
+        );
+
+        let config = Config {
+            sponsor: config,
+            ..Config::default()
+        };
+        assert_eq!(config.validate(), [format!("sponsor.patterns: {}", error)]);
+    }
+}
@@ -0,0 +1,289 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Tells scheduled premieres and live streams from regular uploads
+ *
+*/
+
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::config::StreamConfig;
+use crate::enrich::contains_word;
+use crate::entry::Entry;
+use crate::timestamp::Timestamp;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamState {
+    // Scheduled, nobody has watched it yet
+    Upcoming,
+    Live,
+    // Over and kept as a recording
+    Ended,
+}
+
+impl StreamState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StreamState::Upcoming => "upcoming",
+            StreamState::Live => "live",
+            StreamState::Ended => "ended",
+        }
+    }
+}
+
+impl std::str::FromStr for StreamState {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<StreamState, String> {
+        <StreamState as ValueEnum>::from_str(s, true)
+    }
+}
+
+impl std::fmt::Display for StreamState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub struct StreamRules {
+    // Lowercased, matched as whole words in titles
+    markers: Vec<String>,
+    window_millis: i64,
+}
+
+impl StreamRules {
+    pub fn new(config: &StreamConfig) -> StreamRules {
+        StreamRules {
+            markers: config.markers.iter().map(|m| m.to_lowercase()).collect(),
+            window_millis: i64::try_from(config.window_secs)
+                .unwrap_or(i64::MAX / 1000)
+                .saturating_mul(1000),
+        }
+    }
+
+    fn marked(&self, title: &str) -> bool {
+        let title = title.to_lowercase();
+        self.markers
+            .iter()
+            .any(|marker| contains_word(&title, marker))
+    }
+
+    // Zero views is what feeds show for a premiere or stream that has not started,
+    // but also for any upload seen right after it went up. It only counts with a
+    // marked title, or an entry updated since it was published the way scheduled
+    // ones are. Past that only titles tell streams apart, and one still updated
+    // within the window of being published is taken to be on air.
+    pub fn detect(
+        &self,
+        title: Option<&str>,
+        views: Option<u64>,
+        published: Option<Timestamp>,
+        updated: Option<Timestamp>,
+    ) -> Option<StreamState> {
+        let marked = title.is_some_and(|title| self.marked(title));
+        let delta = published
+            .zip(updated)
+            .map(|(published, updated)| updated.millis() - published.millis());
+        if views == Some(0) && (marked || delta.is_some_and(|delta| delta > 0)) {
+            return Some(StreamState::Upcoming);
+        }
+        if !marked {
+            return None;
+        }
+
+        match delta {
+            Some(delta) if delta > self.window_millis => Some(StreamState::Ended),
+            _ => Some(StreamState::Live),
+        }
+    }
+}
+
+// A video changing stream state from one cached snapshot to the next
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct Transition {
+    pub video_id: String,
+    pub channel_id: String,
+    pub title: String,
+    pub from: Option<StreamState>,
+    pub to: Option<StreamState>,
+    // Updated date of the snapshot the new state was first seen in
+    pub at: Timestamp,
+}
+
+// Snapshots of a video are ordered by their updated date, published when
+// missing; videos that never had a stream state are left out. Titles often
+// lose their live marker afterwards, so a snapshot with no state following
+// one with a state is read as the ended recording.
+pub fn transitions(entries: &[Entry]) -> Vec<Transition> {
+    let mut videos: HashMap<&str, Vec<&Entry>> = HashMap::new();
+    for entry in entries {
+        videos.entry(&entry.video_id).or_default().push(entry);
+    }
+
+    let mut transitions = Vec::new();
+    for snapshots in videos.values_mut() {
+        if snapshots.iter().all(|entry| entry.stream.is_none()) {
+            continue;
+        }
+        snapshots.sort_by_key(|entry| entry.updated.unwrap_or(entry.published));
+        let mut state = snapshots[0].stream;
+        for entry in &snapshots[1..] {
+            let next = entry.stream.or(state.map(|_| StreamState::Ended));
+            if next != state {
+                transitions.push(Transition {
+                    video_id: entry.video_id.clone(),
+                    channel_id: entry.channel_id.clone(),
+                    title: entry.title.clone(),
+                    from: state,
+                    to: next,
+                    at: entry.updated.unwrap_or(entry.published),
+                });
+            }
+            state = next;
+        }
+    }
+
+    transitions.sort_by(|a, b| (a.at, &a.video_id).cmp(&(b.at, &b.video_id)));
+    transitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::entries;
+
+    fn rules() -> StreamRules {
+        StreamRules::new(&StreamConfig::default())
+    }
+
+    fn at(s: &str) -> Option<Timestamp> {
+        Some(s.parse().unwrap())
+    }
+
+    fn detect(entry: &Entry) -> Option<StreamState> {
+        rules().detect(
+            Some(&entry.title),
+            entry.views,
+            Some(entry.published),
+            entry.updated,
+        )
+    }
+
+    fn fixture() -> Vec<Entry> {
+        entries(include_str!("../data/src/streams.xml"))
+    }
+
+    #[test]
+    fn fixture_states() {
+        let states: Vec<(String, Option<StreamState>)> = fixture()
+            .iter()
+            .map(|entry| (entry.video_id.clone(), detect(entry)))
+            .collect();
+        assert_eq!(
+            states,
+            [
+                ("Pr3m1ere001".to_string(), Some(StreamState::Upcoming)),
+                ("L1veN0w0002".to_string(), Some(StreamState::Live)),
+                ("3ndedStr003".to_string(), Some(StreamState::Ended)),
+                ("Del1very004".to_string(), None),
+            ]
+        );
+    }
+
+    // Uploads seen before anyone watched them are not premieres
+    #[test]
+    fn zero_views_alone_is_not_upcoming() {
+        let published = at("2022-11-08T12:00:00+00:00");
+        let rules = rules();
+        assert_eq!(
+            rules.detect(Some("Overtime 32"), Some(0), published, published),
+            None
+        );
+        assert_eq!(
+            rules.detect(Some("Overtime 32"), Some(0), published, None),
+            None
+        );
+        assert_eq!(
+            rules.detect(Some("Overtime 32 premiere"), Some(0), published, published),
+            Some(StreamState::Upcoming)
+        );
+        assert_eq!(
+            rules.detect(
+                Some("Overtime 32"),
+                Some(0),
+                published,
+                at("2022-11-08T12:05:00+00:00")
+            ),
+            Some(StreamState::Upcoming)
+        );
+    }
+
+    #[test]
+    fn markers_match_whole_words() {
+        let rules = rules();
+        assert_eq!(
+            rules.detect(Some("Pizza delivery"), Some(5), None, None),
+            None
+        );
+        assert_eq!(
+            rules.detect(Some("Going LIVE now"), Some(5), None, None),
+            Some(StreamState::Live)
+        );
+    }
+
+    fn snapshot(entry: &Entry, title: &str, views: u64, updated: &str) -> Entry {
+        let mut entry = Entry {
+            title: title.to_string(),
+            views: Some(views),
+            updated: at(updated),
+            ..entry.clone()
+        };
+        entry.stream = detect(&entry);
+        entry
+    }
+
+    #[test]
+    fn transitions_between_snapshots() {
+        let entries = fixture();
+        let premiere = &entries[0];
+        let upload = &entries[3];
+        let snapshots = [
+            snapshot(premiere, "Overtime 32", 0, "2022-11-08T12:05:00+00:00"),
+            snapshot(
+                premiere,
+                "Overtime 32 LIVE",
+                120,
+                "2022-11-08T18:00:00+00:00",
+            ),
+            // The marker is gone once the recording is up
+            snapshot(premiere, "Overtime 32", 90000, "2022-11-09T18:00:00+00:00"),
+            // A regular upload first seen with no views
+            snapshot(upload, &upload.title, 0, "2022-10-30T18:00:00+00:00"),
+            snapshot(upload, &upload.title, 1500, "2022-10-30T20:00:00+00:00"),
+        ];
+
+        let transitions = transitions(&snapshots);
+        let found: Vec<(&str, Option<StreamState>, Option<StreamState>)> = transitions
+            .iter()
+            .map(|t| (t.video_id.as_str(), t.from, t.to))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "Pr3m1ere001",
+                    Some(StreamState::Upcoming),
+                    Some(StreamState::Live)
+                ),
+                (
+                    "Pr3m1ere001",
+                    Some(StreamState::Live),
+                    Some(StreamState::Ended)
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Totals for a parse run, logged when it finishes
+ *
+*/
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use tracing::{info, warn};
+
+use crate::ingest::Outcome;
+use crate::Result;
+
+pub struct Summary {
+    started: Instant,
+    pub files_ingested: usize,
+    pub files_unchanged: usize,
+    pub entries_written: usize,
+    pub tombstones_written: usize,
+    pub memberships_written: usize,
+    pub duplicates_skipped: usize,
+    pub incomplete_skipped: usize,
+    pub incomplete_nulls: usize,
+    pub incomplete_quarantined: usize,
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+impl Default for Summary {
+    fn default() -> Summary {
+        Summary {
+            started: Instant::now(),
+            files_ingested: 0,
+            files_unchanged: 0,
+            entries_written: 0,
+            tombstones_written: 0,
+            memberships_written: 0,
+            duplicates_skipped: 0,
+            incomplete_skipped: 0,
+            incomplete_nulls: 0,
+            incomplete_quarantined: 0,
+            failures: Vec::new(),
+        }
+    }
+}
+
+impl Summary {
+    pub fn add(&mut self, file: &Path, outcome: &Result<Outcome>) {
+        match outcome {
+            Ok(Outcome::Unchanged) | Ok(Outcome::Touched(..)) => self.files_unchanged += 1,
+            Ok(Outcome::Ingested(_, _, stats)) => {
+                self.files_ingested += 1;
+                self.entries_written += stats.entries;
+                self.tombstones_written += stats.tombstones;
+                self.memberships_written += stats.memberships;
+                self.duplicates_skipped += stats.duplicates;
+                self.incomplete_skipped += stats.skipped;
+                self.incomplete_nulls += stats.nulls;
+                self.incomplete_quarantined += stats.quarantined;
+            }
+            Err(e) => self.failures.push((file.to_path_buf(), e.to_string())),
+        }
+    }
+
+    pub fn log(&self) {
+        for (file, reason) in &self.failures {
+            warn!(file = %file.display(), reason = %reason, "file failed");
+        }
+
+        info!(
+            files_ingested = self.files_ingested,
+            files_unchanged = self.files_unchanged,
+            files_failed = self.failures.len(),
+            entries_written = self.entries_written,
+            tombstones_written = self.tombstones_written,
+            memberships_written = self.memberships_written,
+            duplicates_skipped = self.duplicates_skipped,
+            incomplete_skipped = self.incomplete_skipped,
+            incomplete_nulls = self.incomplete_nulls,
+            incomplete_quarantined = self.incomplete_quarantined,
+            elapsed_secs = self.started.elapsed().as_secs_f64(),
+            "run summary"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingest::FileStats;
+    use crate::manifest::Ingested;
+
+    #[test]
+    fn outcomes_add_up() {
+        let ingested = Ingested {
+            size: 1,
+            mtime: 1,
+            md5: "0123456789abcdef0123456789abcdef".to_string(),
+        };
+        let stats = FileStats {
+            entries: 3,
+            tombstones: 1,
+            memberships: 4,
+            duplicates: 2,
+            skipped: 1,
+            nulls: 2,
+            quarantined: 3,
+        };
+
+        let mut summary = Summary::default();
+        let file = Path::new("feed.xml");
+        summary.add(file, &Ok(Outcome::Unchanged));
+        summary.add(
+            file,
+            &Ok(Outcome::Touched(file.to_path_buf(), ingested.clone())),
+        );
+        summary.add(
+            file,
+            &Ok(Outcome::Ingested(
+                file.to_path_buf(),
+                ingested.clone(),
+                stats,
+            )),
+        );
+        summary.add(
+            file,
+            &Ok(Outcome::Ingested(file.to_path_buf(), ingested, stats)),
+        );
+        summary.add(Path::new("bad.xml"), &Err("not xml".into()));
+
+        assert_eq!(summary.files_unchanged, 2);
+        assert_eq!(summary.files_ingested, 2);
+        assert_eq!(summary.entries_written, 6);
+        assert_eq!(summary.tombstones_written, 2);
+        assert_eq!(summary.memberships_written, 8);
+        assert_eq!(summary.duplicates_skipped, 4);
+        assert_eq!(summary.incomplete_skipped, 2);
+        assert_eq!(summary.incomplete_nulls, 4);
+        assert_eq!(summary.incomplete_quarantined, 6);
+        assert_eq!(
+            summary.failures,
+            [(PathBuf::from("bad.xml"), "not xml".to_string())]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Feed dates, compared in utc but written back with the offset they came with
+ *
+*/
+
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Result;
+
+// Equality and ordering go by the instant, so the same moment written with two
+// offsets compares equal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(DateTime<FixedOffset>);
+
+impl Timestamp {
+    // Milliseconds since the unix epoch
+    pub fn millis(&self) -> i64 {
+        self.0.timestamp_millis()
+    }
+
+    // Offset from utc the date was written with, in seconds east
+    pub fn offset_secs(&self) -> i32 {
+        self.0.offset().local_minus_utc()
+    }
+
+    pub fn from_millis(millis: i64, offset_secs: i32) -> Result<Timestamp> {
+        let offset = FixedOffset::east_opt(offset_secs)
+            .ok_or_else(|| format!("offset of {} seconds is out of range", offset_secs))?;
+        let utc = DateTime::from_timestamp_millis(millis)
+            .ok_or_else(|| format!("{} milliseconds is out of range", millis))?;
+        Ok(Timestamp(utc.with_timezone(&offset)))
+    }
+
+    // The date format of rss 2.0, e.g. "Tue, 08 Nov 2022 18:00:00 GMT"
+    pub fn from_rfc2822(s: &str) -> std::result::Result<Timestamp, String> {
+        DateTime::parse_from_rfc2822(s.trim())
+            .map(Timestamp)
+            .map_err(|e| format!("{:?} is not an rfc 2822 date: {}", s, e))
+    }
+}
+
+impl std::str::FromStr for Timestamp {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Timestamp, String> {
+        DateTime::parse_from_rfc3339(s.trim())
+            .map(Timestamp)
+            .map_err(|e| format!("{:?} is not an rfc 3339 date: {}", s, e))
+    }
+}
+
+// Offsets are written as +00:00 rather than Z, the way youtube feeds write them
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0.to_rfc3339_opts(SecondsFormat::AutoSi, false))
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Timestamp, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_3339_keeps_its_offset() {
+        let timestamp: Timestamp = "2022-11-05T15:59:57+02:00".parse().unwrap();
+        assert_eq!(timestamp.offset_secs(), 2 * 60 * 60);
+        assert_eq!(timestamp.to_string(), "2022-11-05T15:59:57+02:00");
+        // Z is written back as +00:00
+        let utc: Timestamp = "2022-11-05T13:59:57Z".parse().unwrap();
+        assert_eq!(utc.to_string(), "2022-11-05T13:59:57+00:00");
+        assert_eq!(timestamp, utc);
+    }
+
+    #[test]
+    fn rfc_2822_dates_of_rss() {
+        let gmt = Timestamp::from_rfc2822("Tue, 08 Nov 2022 18:00:00 GMT").unwrap();
+        assert_eq!(gmt.to_string(), "2022-11-08T18:00:00+00:00");
+        let pacific = Timestamp::from_rfc2822("Tue, 08 Nov 2022 10:00:00 -0800").unwrap();
+        assert_eq!(pacific.to_string(), "2022-11-08T10:00:00-08:00");
+        assert_eq!(gmt, pacific);
+        assert!(Timestamp::from_rfc2822("2022-11-08T18:00:00+00:00").is_err());
+    }
+
+    #[test]
+    fn invalid_dates_are_errors() {
+        for text in ["", "yesterday", "2022-11-05", "2022-13-05T13:59:57+00:00"] {
+            assert!(text.parse::<Timestamp>().is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn millis_and_offset_round_trip() {
+        let timestamp: Timestamp = "2022-11-05T08:59:57.250-05:00".parse().unwrap();
+        let again = Timestamp::from_millis(timestamp.millis(), timestamp.offset_secs()).unwrap();
+        assert_eq!(again.to_string(), timestamp.to_string());
+        assert!(Timestamp::from_millis(0, 24 * 60 * 60).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Cleaned up copy of titles for comparing and searching them
+ *
+*/
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::config::TitleConfig;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum NormalForm {
+    // Canonical composition, é written one way whatever the feed sent
+    Nfc,
+    // Compatibility composition, also folds ligatures, full width and styled letters
+    Nfkc,
+}
+
+impl NormalForm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NormalForm::Nfc => "nfc",
+            NormalForm::Nfkc => "nfkc",
+        }
+    }
+}
+
+impl std::str::FromStr for NormalForm {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<NormalForm, String> {
+        <NormalForm as ValueEnum>::from_str(s, true)
+    }
+}
+
+impl std::fmt::Display for NormalForm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub struct TitleCleaner {
+    normalize: Option<NormalForm>,
+    strip_controls: bool,
+}
+
+impl TitleCleaner {
+    pub fn new(config: &TitleConfig) -> TitleCleaner {
+        TitleCleaner {
+            normalize: config.normalize,
+            strip_controls: config.strip_controls,
+        }
+    }
+
+    // None when no cleaning is configured, so entries only get a normalized
+    // title when asked for
+    pub fn clean(&self, title: &str) -> Option<String> {
+        if self.normalize.is_none() && !self.strip_controls {
+            return None;
+        }
+
+        let stripped = match self.strip_controls {
+            true => strip_controls(title),
+            false => title.to_string(),
+        };
+        Some(match self.normalize {
+            Some(NormalForm::Nfc) => stripped.nfc().collect(),
+            Some(NormalForm::Nfkc) => stripped.nfkc().collect(),
+            None => stripped,
+        })
+    }
+}
+
+// Invisible characters that change nothing on screen: zero width spaces and
+// joiners, direction marks and overrides, the byte order mark, soft hyphens
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{ad}'
+            | '\u{61c}'
+            | '\u{180e}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{206f}'
+            | '\u{feff}'
+    )
+}
+
+// Control characters and invisible ones go, except a zero width joiner inside an
+// emoji sequence like 👨‍👩‍👧. Line breaks and tabs become spaces, and runs of
+// whitespace end up as one space with none at either end.
+fn strip_controls(title: &str) -> String {
+    let chars: Vec<char> = title.chars().collect();
+    let emoji = |c: Option<&char>| {
+        c.is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace() && !c.is_ascii())
+    };
+
+    let mut kept = String::with_capacity(title.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '\u{200d}'
+            && emoji(i.checked_sub(1).and_then(|i| chars.get(i)))
+            && emoji(chars.get(i + 1))
+        {
+            kept.push(c);
+        } else if c.is_whitespace() {
+            kept.push(' ');
+        } else if !c.is_control() && !is_invisible(c) {
+            kept.push(c);
+        }
+    }
+
+    kept.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::entries;
+
+    fn titles() -> Vec<String> {
+        entries(include_str!("../data/src/titles.xml"))
+            .into_iter()
+            .map(|entry| entry.title)
+            .collect()
+    }
+
+    fn cleaner(normalize: Option<NormalForm>, strip_controls: bool) -> TitleCleaner {
+        TitleCleaner::new(&TitleConfig {
+            normalize,
+            strip_controls,
+        })
+    }
+
+    #[test]
+    fn nothing_configured() {
+        assert_eq!(cleaner(None, false).clean(&titles()[0]), None);
+    }
+
+    #[test]
+    fn zero_width_spaces_are_stripped() {
+        let titles = titles();
+        assert_ne!(titles[0], "I Survived 100 Days In Prison");
+        assert_eq!(
+            cleaner(None, true).clean(&titles[0]).as_deref(),
+            Some("I Survived 100 Days In Prison")
+        );
+    }
+
+    #[test]
+    fn nfc_composes_accents() {
+        let titles = titles();
+        let (first, second) = titles[1].split_once(" vs ").unwrap();
+        assert_ne!(first, second.split(' ').next().unwrap());
+
+        let cleaned = cleaner(Some(NormalForm::Nfc), false)
+            .clean(&titles[1])
+            .unwrap();
+        assert_eq!(cleaned, "Caf\u{e9} vs Caf\u{e9} – Which Is Better?");
+    }
+
+    #[test]
+    fn nfkc_folds_compatibility_letters() {
+        let title = &titles()[2];
+        assert_eq!(
+            cleaner(Some(NormalForm::Nfkc), false)
+                .clean(title)
+                .as_deref(),
+            Some("MrBeast final BONUS round")
+        );
+        // Full width and styled letters are canonical already
+        assert_eq!(
+            cleaner(Some(NormalForm::Nfc), false)
+                .clean(title)
+                .as_deref(),
+            Some(title.as_str())
+        );
+    }
+
+    #[test]
+    fn emoji_sequences_survive_stripping() {
+        assert_eq!(
+            cleaner(Some(NormalForm::Nfkc), true)
+                .clean(&titles()[3])
+                .as_deref(),
+            Some("Family Day 👨\u{200d}👩\u{200d}👧 Reversed Together")
+        );
+    }
+}
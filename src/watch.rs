@@ -0,0 +1,244 @@
+/*
+ * This file is part of youtube rss cached project of mine.
+ * Ingests feed files as a fetcher drops them into a spool directory
+ *
+*/
+
+use std::collections::BTreeSet;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use inotify::{EventMask, Inotify, WatchMask};
+use rayon::prelude::*;
+use tracing::{info, warn};
+
+use crate::budget::ByteBudget;
+use crate::discover::Discovery;
+use crate::ingest::{ingest_file, Outcome, Pipeline};
+use crate::manifest::Manifest;
+use crate::sink::Sink;
+use crate::summary::Summary;
+use crate::Result;
+
+pub struct Watcher<'a> {
+    pub spool: PathBuf,
+    pub discovery: &'a Discovery,
+    pub sink: &'a Sink,
+    pub manifest: Manifest,
+    pub save_manifest: bool,
+    pub budget: &'a ByteBudget,
+    pub pipeline: &'a Pipeline,
+    // Successfully parsed files are moved here when set
+    pub processed: Option<PathBuf>,
+    // Files that failed to parse are moved here when set
+    pub failed: Option<PathBuf>,
+}
+
+// rename, falling back to copy and remove when the folders are on different filesystems
+fn move_into(file: &Path, dir: &Path) -> Result<PathBuf> {
+    let name = file.file_name().ok_or("no file name")?;
+    let target = dir.join(name);
+    if std::fs::rename(file, &target).is_err() {
+        std::fs::copy(file, &target)?;
+        std::fs::remove_file(file)?;
+    }
+
+    Ok(target)
+}
+
+// A file that cannot be moved stays in the spool with a warning, rather than
+// stopping the watcher; true when it was moved
+fn move_or_keep(file: &Path, dir: &Path) -> bool {
+    match move_into(file, dir) {
+        Ok(_) => true,
+        Err(e) => {
+            warn!(file = %file.display(), dir = %dir.display(), error = %e, "could not move, left in place");
+            false
+        }
+    }
+}
+
+impl Watcher<'_> {
+    // Files waiting in the spool directory; only its top level is watched
+    fn scan(&self) -> Result<BTreeSet<PathBuf>> {
+        let mut files = BTreeSet::new();
+        for entry in std::fs::read_dir(&self.spool)? {
+            let path = entry?.path();
+            if path.is_file() && self.is_wanted(&path) {
+                files.insert(path);
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn is_wanted(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.spool)
+            .is_ok_and(|relative| self.discovery.wanted(relative))
+    }
+
+    // Parse one batch in parallel; a path seen twice in a burst is only in the set once
+    fn process(&mut self, batch: BTreeSet<PathBuf>) -> Result<()> {
+        let files: Vec<PathBuf> = batch.into_iter().filter(|file| file.is_file()).collect();
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let outcomes: Vec<Result<Outcome>> = files
+            .par_iter()
+            .map(|file| {
+                ingest_file(
+                    file,
+                    &self.manifest,
+                    false,
+                    self.sink,
+                    self.budget,
+                    self.pipeline,
+                )
+            })
+            .collect();
+        self.sink.flush()?;
+
+        let mut summary = Summary::default();
+        for (file, outcome) in files.iter().zip(outcomes) {
+            summary.add(file, &outcome);
+            match outcome {
+                Ok(outcome) => {
+                    // Files left in the spool are recorded so they are not parsed again
+                    let moved = self
+                        .processed
+                        .as_ref()
+                        .is_some_and(|dir| move_or_keep(file, dir));
+                    match outcome {
+                        Outcome::Touched(key, ingested) | Outcome::Ingested(key, ingested, _)
+                            if !moved =>
+                        {
+                            self.manifest.record(key, ingested)
+                        }
+                        _ => (),
+                    }
+                }
+                Err(_) => {
+                    if let Some(dir) = &self.failed {
+                        move_or_keep(file, dir);
+                    }
+                }
+            }
+        }
+
+        if self.save_manifest {
+            self.manifest.save()?;
+        }
+        summary.log();
+        Ok(())
+    }
+
+    // Process what is already waiting, then every file closed after writing or moved
+    // into the spool directory. Runs until an error.
+    pub fn run(mut self) -> Result<()> {
+        let mut inotify = Inotify::init()?;
+        inotify
+            .watches()
+            .add(&self.spool, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO)?;
+
+        // Watch before the first scan so nothing landing in between is missed
+        let waiting = self.scan()?;
+        info!(spool = %self.spool.display(), waiting = waiting.len(), "watching");
+        self.process(waiting)?;
+
+        let mut buffer = [0; 4096];
+        loop {
+            let mut batch = BTreeSet::new();
+            let mut overflow = false;
+            for event in inotify.read_events_blocking(&mut buffer)? {
+                overflow |= event.mask.contains(EventMask::Q_OVERFLOW);
+                if let Some(name) = event.name {
+                    batch.insert(self.spool.join(name));
+                }
+            }
+
+            // Drain whatever else arrived in the same burst before parsing
+            loop {
+                match inotify.read_events(&mut buffer) {
+                    Ok(events) => {
+                        for event in events {
+                            overflow |= event.mask.contains(EventMask::Q_OVERFLOW);
+                            if let Some(name) = event.name {
+                                batch.insert(self.spool.join(name));
+                            }
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            // Events were dropped by the kernel, fall back to looking at the directory
+            if overflow {
+                warn!(spool = %self.spool.display(), "inotify queue overflowed, rescanning");
+                batch.extend(self.scan()?);
+            }
+
+            batch.retain(|path| self.is_wanted(path));
+            self.process(batch)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::Cache;
+    use crate::config::{Config, InputConfig};
+    use crate::format::Format;
+
+    const FEED: &str = r#"<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015">
+ <entry>
+  <yt:videoId>Ab3CdEfGhIj</yt:videoId>
+  <yt:channelId>UCRijo3ddMTht_IHyNSNXpNQ</yt:channelId>
+  <title>Overtime 30</title>
+  <author><name>Dude Perfect</name></author>
+  <published>2022-11-05T13:59:57+00:00</published>
+ </entry>
+</feed>"#;
+
+    #[test]
+    fn unmovable_files_stay_and_are_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let (spool, cache) = (dir.path().join("spool"), dir.path().join("cache"));
+        std::fs::create_dir(&spool).unwrap();
+        std::fs::create_dir(&cache).unwrap();
+        let feed = spool.join("feed.xml");
+        std::fs::write(&feed, FEED).unwrap();
+
+        assert!(!move_or_keep(&feed, &dir.path().join("missing")));
+        assert!(feed.is_file());
+
+        let discovery = Discovery::new(&InputConfig::default()).unwrap();
+        let sink = Sink::Cache(Cache::new(&cache, Format::Json, 0));
+        let budget = ByteBudget::new(1024);
+        let pipeline = Pipeline::new(&Config::default()).unwrap();
+        let mut watcher = Watcher {
+            spool: spool.clone(),
+            discovery: &discovery,
+            sink: &sink,
+            budget: &budget,
+            pipeline: &pipeline,
+            manifest: Manifest::default(),
+            save_manifest: false,
+            processed: Some(dir.path().join("missing")),
+            failed: None,
+        };
+        watcher.process(watcher.scan().unwrap()).unwrap();
+        assert!(feed.is_file());
+        let key = std::fs::canonicalize(&feed).unwrap();
+        assert!(watcher.manifest.get(&key).is_some());
+        assert_eq!(std::fs::read_dir(&cache).unwrap().count(), 1);
+
+        // Once the folder is there the file moves
+        std::fs::create_dir(dir.path().join("processed")).unwrap();
+        assert!(move_or_keep(&feed, &dir.path().join("processed")));
+        assert!(!feed.exists());
+        assert!(dir.path().join("processed/feed.xml").is_file());
+    }
+}